@@ -69,19 +69,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     //now we are ready to create a CoreContext. The context is the heart of the JEB Api.
     let context = core_service.createEnginesContext(Some(data_provider.as_ref()), None)?;
 
-    //fine, let's start with a new project
+    //fine, let's load the project, JEB restores it if it was saved in the project folder before
     let prj = context.loadProject(PROJECT_NAME)?;
-    println!("Created project: {}", prj.getKey()?);
-
-    //we need an artifact
-    let artifact = Artifact::new("examples/artifacts/test.apk")?;
+    let live_artifact = if prj.isReloaded()? {
+        println!("Restored project: {}", prj.getKey()?);
+        //a restored project already holds our artifact
+        prj.getLiveArtifacts()?
+            .into_iter()
+            .next()
+            .ok_or("restored project has no artifact")?
+    } else {
+        println!("Created project: {}", prj.getKey()?);
+        //we need an artifact
+        let artifact = Artifact::new("examples/artifacts/test.apk")?;
+        prj.processArtifact(Some(artifact.as_ref()))?
+    };
 
     //first let's find the reference to testFunction() to set a breakpoint later on
-    let live_artifact = prj.processArtifact(Some(artifact.as_ref()))?;
-    //hold a reference to all the units
-    let units = live_artifact.getUnits()?;
-    //get apk unit
-    let apk_unit = units.first().unwrap();
+    //the main unit of the artifact is the apk unit
+    let apk_unit = live_artifact
+        .getMainUnit()?
+        .ok_or("artifact has not been processed")?;
     //hold references to children
     let children = apk_unit.getChildren()?;
     //find dex unit
@@ -412,7 +420,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             }
                         }
                         // see if we find the methodd in the codeunit...
-                        if let Ok(method) = dex_unit.getMethodByName(data) {
+                        if let Some(method) = dex_unit.getMethodByName(data)? {
                             //... so we can get all instructions...
                             let instructions = method.getInstructions().unwrap();
                             println!("\n\n{} {{", method.getSignature(false)?);
@@ -457,6 +465,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     //we are finished detach the debugger
     debug_unit.detach()?;
+    //save the project, so the next run can restore it
+    context.saveProject(prj.getKey()?.as_str(), None)?;
     //unload the project...
     context.unloadProject(prj.getKey()?.as_str())?;
     //... and close the engines context
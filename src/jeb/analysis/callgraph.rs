@@ -0,0 +1,605 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Whole-app call graph over the methods of a dex unit. Virtual and interface calls get an edge to
+//! the invoked method and to every override in the class hierarchy, so the graph over-approximates
+//! dispatch.
+
+use crate::jeb::analysis::hierarchy::ClassHierarchy;
+use crate::jeb::analysis::manifest::AndroidManifest;
+use crate::jeb::com::pnfsoftware::jeb::core::units::code::android::dex::{
+    DalvikOpcode, DalvikOperand,
+};
+use crate::jeb::com::pnfsoftware::jeb::core::units::IDexUnit;
+use crate::jeb::*;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dispatch {
+    /// Static, direct and super calls, and the invoked method of a virtual call.
+    Direct,
+    /// An override the virtual or interface call may dispatch to.
+    Virtual,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallEdge {
+    pub caller: usize,
+    pub callee: usize,
+    /// The offset of the invoke instruction in the caller.
+    pub offset: i64,
+    pub dispatch: Dispatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallGraphNode {
+    /// The original signature, e.g. `Lcom/example/Foo;->bar(I)V`.
+    pub signature: String,
+    /// Whether the method is defined in the dex unit, as opposed to e.g. a framework method.
+    pub internal: bool,
+    pub entry_point: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    pub nodes: Vec<CallGraphNode>,
+    pub edges: Vec<CallEdge>,
+    indexes: HashMap<String, usize>,
+    outgoing: Vec<Vec<usize>>,
+    incoming: Vec<Vec<usize>>,
+}
+
+/// The framework callbacks of activities, besides the constructor.
+const ACTIVITY_CALLBACKS: &[&str] = &[
+    "onCreate(Landroid/os/Bundle;)V",
+    "onPostCreate(Landroid/os/Bundle;)V",
+    "onStart()V",
+    "onRestart()V",
+    "onResume()V",
+    "onPostResume()V",
+    "onPause()V",
+    "onStop()V",
+    "onDestroy()V",
+    "onNewIntent(Landroid/content/Intent;)V",
+    "onActivityResult(IILandroid/content/Intent;)V",
+    "onRequestPermissionsResult(I[Ljava/lang/String;[I)V",
+    "onSaveInstanceState(Landroid/os/Bundle;)V",
+    "onRestoreInstanceState(Landroid/os/Bundle;)V",
+    "onConfigurationChanged(Landroid/content/res/Configuration;)V",
+    "onWindowFocusChanged(Z)V",
+    "onBackPressed()V",
+    "onCreateOptionsMenu(Landroid/view/Menu;)Z",
+    "onOptionsItemSelected(Landroid/view/MenuItem;)Z",
+    "onKeyDown(ILandroid/view/KeyEvent;)Z",
+    "onTouchEvent(Landroid/view/MotionEvent;)Z",
+];
+
+const SERVICE_CALLBACKS: &[&str] = &[
+    "onCreate()V",
+    "onStart(Landroid/content/Intent;I)V",
+    "onStartCommand(Landroid/content/Intent;II)I",
+    "onBind(Landroid/content/Intent;)Landroid/os/IBinder;",
+    "onUnbind(Landroid/content/Intent;)Z",
+    "onRebind(Landroid/content/Intent;)V",
+    "onTaskRemoved(Landroid/content/Intent;)V",
+    "onHandleIntent(Landroid/content/Intent;)V",
+    "onConfigurationChanged(Landroid/content/res/Configuration;)V",
+    "onLowMemory()V",
+    "onTrimMemory(I)V",
+    "onDestroy()V",
+];
+
+const RECEIVER_CALLBACKS: &[&str] = &["onReceive(Landroid/content/Context;Landroid/content/Intent;)V"];
+
+const PROVIDER_CALLBACKS: &[&str] = &[
+    "onCreate()Z",
+    "query(Landroid/net/Uri;[Ljava/lang/String;Ljava/lang/String;[Ljava/lang/String;Ljava/lang/String;)Landroid/database/Cursor;",
+    "insert(Landroid/net/Uri;Landroid/content/ContentValues;)Landroid/net/Uri;",
+    "update(Landroid/net/Uri;Landroid/content/ContentValues;Ljava/lang/String;[Ljava/lang/String;)I",
+    "delete(Landroid/net/Uri;Ljava/lang/String;[Ljava/lang/String;)I",
+    "getType(Landroid/net/Uri;)Ljava/lang/String;",
+    "call(Ljava/lang/String;Ljava/lang/String;Landroid/os/Bundle;)Landroid/os/Bundle;",
+    "openFile(Landroid/net/Uri;Ljava/lang/String;)Landroid/os/ParcelFileDescriptor;",
+];
+
+const APPLICATION_CALLBACKS: &[&str] = &[
+    "attachBaseContext(Landroid/content/Context;)V",
+    "onCreate()V",
+    "onTerminate()V",
+    "onConfigurationChanged(Landroid/content/res/Configuration;)V",
+    "onLowMemory()V",
+    "onTrimMemory(I)V",
+];
+
+/// The component classes of `manifest` as type signatures, with the callbacks the framework
+/// invokes on them.
+fn component_callbacks(manifest: &AndroidManifest) -> Vec<(String, &'static [&'static str])> {
+    let kinds: [(&[String], &'static [&'static str]); 4] = [
+        (&manifest.activities, ACTIVITY_CALLBACKS),
+        (&manifest.services, SERVICE_CALLBACKS),
+        (&manifest.receivers, RECEIVER_CALLBACKS),
+        (&manifest.providers, PROVIDER_CALLBACKS),
+    ];
+    let application = manifest
+        .application
+        .iter()
+        .map(|application| (application, APPLICATION_CALLBACKS));
+    kinds
+        .iter()
+        .flat_map(|(classes, callbacks)| classes.iter().map(move |class| (class, *callbacks)))
+        .chain(application)
+        .map(|(class, callbacks)| (format!("L{};", class.replace('.', "/")), callbacks))
+        .collect()
+}
+
+/// Splits `Lcom/example/Foo;->bar(I)V` into the class and `bar(I)V`.
+fn split_method_signature(signature: &str) -> Option<(&str, &str)> {
+    let arrow = signature.find("->")?;
+    Some((&signature[..arrow], &signature[arrow + 2..]))
+}
+
+impl CallGraph {
+    /// Builds the call graph of `unit`. The constructors and framework callbacks of the manifest
+    /// components and all static initializers are entry points.
+    pub fn build(unit: &dyn IDexUnit, manifest: Option<&AndroidManifest>) -> Result<'static, CallGraph> {
+        let hierarchy = ClassHierarchy::from_unit(unit)?;
+        CallGraph::build_with_hierarchy(unit, &hierarchy, manifest)
+    }
+
+    /// Builds the call graph of `unit`, resolving virtual dispatch over `hierarchy`, which may
+    /// hold more classes than `unit`.
+    pub fn build_with_hierarchy(
+        unit: &dyn IDexUnit,
+        hierarchy: &ClassHierarchy,
+        manifest: Option<&AndroidManifest>,
+    ) -> Result<'static, CallGraph> {
+        let mut graph = CallGraph::default();
+        let mut methods = vec![];
+        for method in unit.getMethods()? {
+            if method.isInternal()? {
+                graph.add_node(&method.getSignature(false)?, true);
+                methods.push(method);
+            }
+        }
+
+        graph.mark_entry_points(hierarchy, manifest);
+
+        for method in methods {
+            let caller = graph.add_node(&method.getSignature(false)?, true);
+            for instruction in method.getDalvikInstructions()? {
+                let opcode = instruction.getOpcode()?;
+                if !opcode.is_invoke() || matches!(opcode, DalvikOpcode::InvokeCustom | DalvikOpcode::InvokeCustomRange) {
+                    continue;
+                }
+                let index = instruction.getOperands()?.into_iter().find_map(|operand| match operand {
+                    DalvikOperand::Index(index) => Some(index as i32),
+                    _ => None,
+                });
+                let index = match index {
+                    Some(index) => index,
+                    None => continue,
+                };
+                let offset = instruction.getOffset()?;
+                let target = unit.getMethodByIndex(index)?.getSignature(false)?;
+                let callee = graph.add_node(&target, false);
+                graph.add_edge(caller, callee, offset, Dispatch::Direct);
+
+                let is_virtual = matches!(
+                    opcode,
+                    DalvikOpcode::InvokeVirtual
+                        | DalvikOpcode::InvokeVirtualRange
+                        | DalvikOpcode::InvokeInterface
+                        | DalvikOpcode::InvokeInterfaceRange
+                );
+                if !is_virtual {
+                    continue;
+                }
+                let mut candidates = hierarchy.overrides(&target);
+                if let Some((class, sub_signature)) = split_method_signature(&target) {
+                    // the invoked method may be inherited rather than declared by its class
+                    if let Some(resolved) = hierarchy.resolve_method(class, sub_signature) {
+                        if resolved != target {
+                            candidates.push(resolved);
+                        }
+                    }
+                }
+                for candidate in candidates {
+                    let candidate = graph.add_node(&candidate, false);
+                    graph.add_edge(caller, candidate, offset, Dispatch::Virtual);
+                }
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Marks the static initializers, and the constructors and callbacks of the components.
+    fn mark_entry_points(&mut self, hierarchy: &ClassHierarchy, manifest: Option<&AndroidManifest>) {
+        for node in self.nodes.iter_mut() {
+            if let Some((_, sub_signature)) = split_method_signature(&node.signature) {
+                node.entry_point = sub_signature.starts_with("<clinit>(");
+            }
+        }
+        for (class, callbacks) in manifest.map(component_callbacks).unwrap_or_default() {
+            for sub_signature in callbacks.iter().copied().chain(Some("<init>()V")) {
+                // the callback may be declared by a superclass of the component, e.g. a base
+                // activity of the app
+                let method = match hierarchy.resolve_method(&class, sub_signature) {
+                    Some(method) => method,
+                    None => continue,
+                };
+                if let Some(index) = self.node_index(&method) {
+                    self.nodes[index].entry_point = true;
+                }
+            }
+        }
+    }
+
+    fn add_node(&mut self, signature: &str, internal: bool) -> usize {
+        if let Some(index) = self.indexes.get(signature) {
+            let node = &mut self.nodes[*index];
+            node.internal |= internal;
+            return *index;
+        }
+        let index = self.nodes.len();
+        self.nodes.push(CallGraphNode {
+            signature: signature.to_string(),
+            internal,
+            entry_point: false,
+        });
+        self.indexes.insert(signature.to_string(), index);
+        self.outgoing.push(vec![]);
+        self.incoming.push(vec![]);
+        index
+    }
+
+    fn add_edge(&mut self, caller: usize, callee: usize, offset: i64, dispatch: Dispatch) {
+        let edge = self.edges.len();
+        self.edges.push(CallEdge {
+            caller,
+            callee,
+            offset,
+            dispatch,
+        });
+        self.outgoing[caller].push(edge);
+        self.incoming[callee].push(edge);
+    }
+
+    pub fn node_index(&self, signature: &str) -> Option<usize> {
+        self.indexes.get(signature).copied()
+    }
+
+    pub fn entry_points(&self) -> Vec<usize> {
+        (0..self.nodes.len()).filter(|index| self.nodes[*index].entry_point).collect()
+    }
+
+    pub fn callees(&self, node: usize) -> impl Iterator<Item = &CallEdge> {
+        self.outgoing[node].iter().map(move |edge| &self.edges[*edge])
+    }
+
+    pub fn callers(&self, node: usize) -> impl Iterator<Item = &CallEdge> {
+        self.incoming[node].iter().map(move |edge| &self.edges[*edge])
+    }
+
+    /// Whether `to` is reachable from each node, by walking the call edges backwards.
+    pub fn reaching(&self, to: usize) -> Vec<bool> {
+        let mut reaching = vec![false; self.nodes.len()];
+        reaching[to] = true;
+        let mut pending = vec![to];
+        while let Some(node) = pending.pop() {
+            for edge in self.callers(node) {
+                if !reaching[edge.caller] {
+                    reaching[edge.caller] = true;
+                    pending.push(edge.caller);
+                }
+            }
+        }
+        reaching
+    }
+
+    /// The acyclic call paths from `from` to `to` with at most `max_depth` calls, at most `limit`
+    /// of them. Only methods from which `to` is reachable are explored, but the number of paths
+    /// can still grow exponentially with `max_depth`.
+    pub fn paths(&self, from: usize, to: usize, limit: usize, max_depth: usize) -> Vec<Vec<usize>> {
+        self.paths_reaching(from, to, limit, max_depth, &self.reaching(to))
+    }
+
+    fn paths_reaching(
+        &self,
+        from: usize,
+        to: usize,
+        limit: usize,
+        max_depth: usize,
+        reaching: &[bool],
+    ) -> Vec<Vec<usize>> {
+        let mut paths = vec![];
+        if !reaching[from] {
+            return paths;
+        }
+        let mut path = vec![from];
+        let mut on_path = HashSet::new();
+        on_path.insert(from);
+        let search = PathSearch {
+            to,
+            limit,
+            max_depth,
+            reaching,
+        };
+        search.collect(self, &mut path, &mut on_path, &mut paths);
+        paths
+    }
+
+    /// The call paths from any entry point to a method whose signature starts with `sink`, e.g.
+    /// `Landroid/telephony/SmsManager;->sendTextMessage`, with at most `max_depth` calls. At most
+    /// `limit` paths per sink method.
+    pub fn paths_to_sink(&self, sink: &str, limit: usize, max_depth: usize) -> Vec<Vec<usize>> {
+        let sinks: Vec<usize> = (0..self.nodes.len())
+            .filter(|index| self.nodes[*index].signature.starts_with(sink))
+            .collect();
+        let entry_points = self.entry_points();
+        let mut paths = vec![];
+        for sink_node in sinks {
+            let reaching = self.reaching(sink_node);
+            let mut sink_paths = vec![];
+            for entry_point in &entry_points {
+                let remaining = limit.saturating_sub(sink_paths.len());
+                if remaining == 0 {
+                    break;
+                }
+                sink_paths.extend(self.paths_reaching(*entry_point, sink_node, remaining, max_depth, &reaching));
+            }
+            paths.extend(sink_paths);
+        }
+        paths
+    }
+
+    /// The graph in the Graphviz DOT language. Entry points are bold, external methods grey and
+    /// virtual dispatch edges dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph callgraph {\n    node [shape=box];\n");
+        for (index, node) in self.nodes.iter().enumerate() {
+            let mut style = vec![];
+            if node.entry_point {
+                style.push("bold");
+            }
+            if !node.internal {
+                style.push("filled");
+            }
+            let _ = writeln!(
+                dot,
+                "    m{} [label=\"{}\"{}];",
+                index,
+                node.signature.replace('"', "\\\""),
+                if style.is_empty() {
+                    String::new()
+                } else {
+                    format!(", style=\"{}\"", style.join(","))
+                }
+            );
+        }
+        for edge in &self.edges {
+            match edge.dispatch {
+                Dispatch::Direct => {
+                    let _ = writeln!(dot, "    m{} -> m{};", edge.caller, edge.callee);
+                }
+                Dispatch::Virtual => {
+                    let _ = writeln!(dot, "    m{} -> m{} [style=dashed];", edge.caller, edge.callee);
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The graph as JSON: `{"nodes": [...], "edges": [...]}`, edges refer to nodes by index.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"nodes\":[");
+        for (index, node) in self.nodes.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            let _ = write!(
+                json,
+                "{{\"signature\":{},\"internal\":{},\"entry_point\":{}}}",
+                super::json_string(&node.signature),
+                node.internal,
+                node.entry_point
+            );
+        }
+        json.push_str("],\"edges\":[");
+        for (index, edge) in self.edges.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            let _ = write!(
+                json,
+                "{{\"caller\":{},\"callee\":{},\"offset\":{},\"dispatch\":\"{}\"}}",
+                edge.caller,
+                edge.callee,
+                edge.offset,
+                match edge.dispatch {
+                    Dispatch::Direct => "direct",
+                    Dispatch::Virtual => "virtual",
+                }
+            );
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+/// The bounds of a depth-first path search in a call graph.
+struct PathSearch<'r> {
+    to: usize,
+    limit: usize,
+    max_depth: usize,
+    reaching: &'r [bool],
+}
+
+impl<'r> PathSearch<'r> {
+    fn collect(
+        &self,
+        graph: &CallGraph,
+        path: &mut Vec<usize>,
+        on_path: &mut HashSet<usize>,
+        paths: &mut Vec<Vec<usize>>,
+    ) {
+        if paths.len() >= self.limit {
+            return;
+        }
+        let current = *path.last().unwrap();
+        if current == self.to {
+            paths.push(path.clone());
+            return;
+        }
+        if path.len() > self.max_depth {
+            return;
+        }
+        let mut callees: Vec<usize> = graph
+            .callees(current)
+            .map(|edge| edge.callee)
+            .filter(|callee| self.reaching[*callee])
+            .collect();
+        callees.sort_unstable();
+        callees.dedup();
+        for callee in callees {
+            if on_path.insert(callee) {
+                path.push(callee);
+                self.collect(graph, path, on_path, paths);
+                path.pop();
+                on_path.remove(&callee);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jeb::analysis::hierarchy::ClassInfo;
+
+    /// A graph over `signatures` with direct calls `edges`, the first node is the entry point.
+    fn graph(signatures: &[&str], edges: &[(usize, usize)]) -> CallGraph {
+        let mut graph = CallGraph::default();
+        for signature in signatures {
+            graph.add_node(signature, true);
+        }
+        graph.nodes[0].entry_point = true;
+        for (offset, (caller, callee)) in edges.iter().enumerate() {
+            graph.add_edge(*caller, *callee, offset as i64 * 2, Dispatch::Direct);
+        }
+        graph
+    }
+
+    #[test]
+    fn nodes_are_deduplicated() {
+        let mut graph = CallGraph::default();
+        let a = graph.add_node("La;->a()V", false);
+        let b = graph.add_node("La;->b()V", false);
+        assert_eq!(graph.add_node("La;->a()V", true), a);
+        assert_ne!(a, b);
+        assert!(graph.nodes[a].internal);
+        assert_eq!(graph.node_index("La;->b()V"), Some(b));
+        assert_eq!(graph.node_index("La;->c()V"), None);
+    }
+
+    #[test]
+    fn reaching_walks_callers() {
+        let graph = graph(&["La;->a()V", "La;->b()V", "La;->c()V", "La;->d()V"], &[(0, 1), (1, 2), (3, 0)]);
+        assert_eq!(graph.reaching(2), vec![true, true, true, true]);
+        assert_eq!(graph.reaching(0), vec![true, false, false, true]);
+        assert_eq!(graph.reaching(3), vec![false, false, false, true]);
+    }
+
+    #[test]
+    fn paths_skip_duplicate_call_sites_and_cycles() {
+        // a calls b twice, b and c call each other, both reach d
+        let graph = graph(
+            &["La;->a()V", "La;->b()V", "La;->c()V", "La;->d()V"],
+            &[(0, 1), (0, 1), (1, 2), (2, 1), (1, 3), (2, 3)],
+        );
+        let mut paths = graph.paths(0, 3, 10, 10);
+        paths.sort();
+        assert_eq!(paths, vec![vec![0, 1, 2, 3], vec![0, 1, 3]]);
+        assert_eq!(graph.paths(0, 3, 1, 10).len(), 1);
+        assert_eq!(graph.paths(0, 3, 10, 2), vec![vec![0, 1, 3]]);
+        assert!(graph.paths(3, 0, 10, 10).is_empty());
+    }
+
+    #[test]
+    fn paths_to_sink_start_at_entry_points() {
+        let mut graph = graph(
+            &["La;->a()V", "La;->b()V", "Landroid/telephony/SmsManager;->sendTextMessage()V", "La;->c()V"],
+            &[(0, 1), (1, 2), (3, 2)],
+        );
+        assert_eq!(graph.paths_to_sink("Landroid/telephony/SmsManager;->", 10, 10), vec![vec![0, 1, 2]]);
+        graph.nodes[3].entry_point = true;
+        assert_eq!(graph.paths_to_sink("Landroid/telephony/SmsManager;->", 10, 10).len(), 2);
+        assert_eq!(graph.paths_to_sink("Landroid/telephony/SmsManager;->", 1, 10).len(), 1);
+        assert!(graph.paths_to_sink("Ljava/lang/Runtime;->exec", 10, 10).is_empty());
+    }
+
+    #[test]
+    fn entry_points_are_component_callbacks() {
+        let class = |signature: &str, superclass: &str, methods: &[&str]| ClassInfo {
+            signature: signature.to_string(),
+            superclass: Some(superclass.to_string()),
+            interfaces: vec![],
+            is_interface: false,
+            methods: methods.iter().map(|method| method.to_string()).collect(),
+        };
+        let mut hierarchy = ClassHierarchy::new();
+        hierarchy.add_class(class(
+            "Lcom/example/BaseActivity;",
+            "Landroid/app/Activity;",
+            &["onCreate(Landroid/os/Bundle;)V"],
+        ));
+        hierarchy.add_class(class(
+            "Lcom/example/MainActivity;",
+            "Lcom/example/BaseActivity;",
+            &["<init>()V", "one()V", "onResume()V"],
+        ));
+        hierarchy.add_class(class("Lcom/example/Util;", "Ljava/lang/Object;", &["<clinit>()V", "onCreate()V"]));
+        let mut graph = graph(
+            &[
+                "Lcom/example/BaseActivity;->onCreate(Landroid/os/Bundle;)V",
+                "Lcom/example/MainActivity;-><init>()V",
+                "Lcom/example/MainActivity;->one()V",
+                "Lcom/example/MainActivity;->onResume()V",
+                "Lcom/example/Util;-><clinit>()V",
+                "Lcom/example/Util;->onCreate()V",
+            ],
+            &[],
+        );
+        let manifest = AndroidManifest {
+            package: "com.example".to_string(),
+            activities: vec!["com.example.MainActivity".to_string()],
+            ..Default::default()
+        };
+        graph.mark_entry_points(&hierarchy, Some(&manifest));
+        assert_eq!(graph.entry_points(), vec![0, 1, 3, 4]);
+        graph.mark_entry_points(&hierarchy, None);
+        assert_eq!(graph.entry_points(), vec![4]);
+    }
+
+    #[test]
+    fn json() {
+        let mut graph = graph(&["La;->a()V", "La;->\"b\"()V"], &[(0, 1)]);
+        graph.nodes[1].internal = false;
+        graph.add_edge(0, 1, 6, Dispatch::Virtual);
+        assert_eq!(
+            graph.to_json(),
+            "{\"nodes\":[\
+             {\"signature\":\"La;->a()V\",\"internal\":true,\"entry_point\":true},\
+             {\"signature\":\"La;->\\\"b\\\"()V\",\"internal\":false,\"entry_point\":false}\
+             ],\"edges\":[\
+             {\"caller\":0,\"callee\":1,\"offset\":0,\"dispatch\":\"direct\"},\
+             {\"caller\":0,\"callee\":1,\"offset\":6,\"dispatch\":\"virtual\"}\
+             ]}"
+        );
+    }
+}
@@ -0,0 +1,321 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Control flow graphs of dex methods, copied from the graph JEB computes for the code item.
+
+use crate::jeb::com::pnfsoftware::jeb::core::units::code::asm::cfg::{BasicBlock, CFG};
+use crate::jeb::com::pnfsoftware::jeb::core::units::IDexMethod;
+use crate::jeb::*;
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// Fall-through, branch or switch case.
+    Regular,
+    /// From a block in a try range to the handler catching its exceptions.
+    Exception,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub first_address: i64,
+    /// The address following the last instruction of the block.
+    pub end_address: i64,
+    pub instruction_offsets: Vec<i64>,
+    pub successors: Vec<usize>,
+    pub predecessors: Vec<usize>,
+    pub exception_successors: Vec<usize>,
+    pub exception_predecessors: Vec<usize>,
+}
+
+impl Block {
+    /// Whether the block is the start of an exception handler.
+    pub fn is_handler(&self) -> bool {
+        !self.exception_predecessors.is_empty()
+    }
+}
+
+/// A block as read from JEB, with its neighbours given by their first address.
+struct BlockAddresses {
+    first_address: i64,
+    end_address: i64,
+    instruction_offsets: Vec<i64>,
+    successors: Vec<i64>,
+    predecessors: Vec<i64>,
+    exception_successors: Vec<i64>,
+    exception_predecessors: Vec<i64>,
+}
+
+/// The basic blocks of a method, blocks refer to each other by their index in `blocks`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<Block>,
+    pub entry: usize,
+}
+
+impl ControlFlowGraph {
+    /// The graph of `method`, `None` if the method has no code.
+    pub fn from_method(method: &dyn IDexMethod) -> Result<'static, Option<ControlFlowGraph>> {
+        match method.getCodeItem()? {
+            Some(code) => Ok(Some(ControlFlowGraph::from_cfg(&code.getControlFlowGraph()?)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn from_cfg(cfg: &CFG) -> Result<'static, ControlFlowGraph> {
+        let addresses = |blocks: Vec<Box<BasicBlock>>| -> Result<'static, Vec<i64>> {
+            blocks.iter().map(|block| block.getFirstAddress()).collect()
+        };
+        let mut blocks = vec![];
+        for block in cfg.getBlocks()? {
+            let mut instruction_offsets = vec![];
+            for idx in 0..block.size()? {
+                instruction_offsets.push(block.get(idx)?.getOffset()?);
+            }
+            blocks.push(BlockAddresses {
+                first_address: block.getFirstAddress()?,
+                end_address: block.getEndAddress()?,
+                instruction_offsets,
+                successors: addresses(block.getOutputs()?)?,
+                predecessors: addresses(block.getInputs()?)?,
+                exception_successors: addresses(block.getIrregularOutputs()?)?,
+                exception_predecessors: addresses(block.getIrregularInputs()?)?,
+            });
+        }
+        ControlFlowGraph::from_addresses(blocks, cfg.getEntryBlock()?.getFirstAddress()?)
+    }
+
+    /// Builds the graph from blocks referring to each other by their first address.
+    fn from_addresses(blocks: Vec<BlockAddresses>, entry_address: i64) -> Result<'static, ControlFlowGraph> {
+        let mut indexes = HashMap::new();
+        for (index, block) in blocks.iter().enumerate() {
+            indexes.insert(block.first_address, index);
+        }
+        let resolve = |addresses: &[i64]| -> Result<'static, Vec<usize>> {
+            let mut resolved = vec![];
+            for address in addresses {
+                match indexes.get(address) {
+                    Some(index) => resolved.push(*index),
+                    None => return Err(format!("no block at {:x}", address).into()),
+                }
+            }
+            Ok(resolved)
+        };
+
+        let mut resolved = vec![];
+        for block in &blocks {
+            resolved.push(Block {
+                first_address: block.first_address,
+                end_address: block.end_address,
+                instruction_offsets: block.instruction_offsets.clone(),
+                successors: resolve(&block.successors)?,
+                predecessors: resolve(&block.predecessors)?,
+                exception_successors: resolve(&block.exception_successors)?,
+                exception_predecessors: resolve(&block.exception_predecessors)?,
+            });
+        }
+        let entry = *indexes
+            .get(&entry_address)
+            .ok_or("entry block is not part of the graph")?;
+        Ok(ControlFlowGraph {
+            blocks: resolved,
+            entry,
+        })
+    }
+
+    /// The block containing the instruction at `address`.
+    pub fn block_at(&self, address: i64) -> Option<usize> {
+        self.blocks
+            .iter()
+            .position(|block| block.first_address <= address && address < block.end_address)
+    }
+
+    /// All edges as `(from, to, kind)`.
+    pub fn edges(&self) -> Vec<(usize, usize, EdgeKind)> {
+        let mut edges = vec![];
+        for (index, block) in self.blocks.iter().enumerate() {
+            for successor in &block.successors {
+                edges.push((index, *successor, EdgeKind::Regular));
+            }
+            for successor in &block.exception_successors {
+                edges.push((index, *successor, EdgeKind::Exception));
+            }
+        }
+        edges
+    }
+
+    /// Whether each block can be reached from `start`, following exception edges too.
+    pub fn reachable_from(&self, start: usize) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut queue = VecDeque::new();
+        queue.push_back(start);
+        while let Some(index) = queue.pop_front() {
+            if reachable[index] {
+                continue;
+            }
+            reachable[index] = true;
+            let block = &self.blocks[index];
+            queue.extend(block.successors.iter().chain(&block.exception_successors));
+        }
+        reachable
+    }
+
+    /// The graph in the Graphviz DOT language, exception edges are dashed.
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = String::new();
+        let _ = writeln!(dot, "digraph \"{}\" {{", name.replace('"', "\\\""));
+        let _ = writeln!(dot, "    node [shape=box];");
+        for (index, block) in self.blocks.iter().enumerate() {
+            let _ = writeln!(
+                dot,
+                "    b{} [label=\"{:x}h-{:x}h\"{}];",
+                index,
+                block.first_address,
+                block.end_address,
+                if index == self.entry { ", style=bold" } else { "" }
+            );
+        }
+        for (from, to, kind) in self.edges() {
+            match kind {
+                EdgeKind::Regular => {
+                    let _ = writeln!(dot, "    b{} -> b{};", from, to);
+                }
+                EdgeKind::Exception => {
+                    let _ = writeln!(dot, "    b{} -> b{} [style=dashed];", from, to);
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The graph as a `petgraph` graph, node `i` is block `i` and carries its first address.
+    #[cfg(feature = "petgraph")]
+    pub fn to_petgraph(&self) -> petgraph::graph::DiGraph<i64, EdgeKind> {
+        let mut graph = petgraph::graph::DiGraph::with_capacity(self.blocks.len(), 0);
+        for block in &self.blocks {
+            graph.add_node(block.first_address);
+        }
+        for (from, to, kind) in self.edges() {
+            graph.add_edge(
+                petgraph::graph::NodeIndex::new(from),
+                petgraph::graph::NodeIndex::new(to),
+                kind,
+            );
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A block at `first_address` of `size` bytes with one instruction per two bytes.
+    fn block(
+        first_address: i64,
+        size: i64,
+        successors: &[i64],
+        predecessors: &[i64],
+        exception_successors: &[i64],
+        exception_predecessors: &[i64],
+    ) -> BlockAddresses {
+        BlockAddresses {
+            first_address,
+            end_address: first_address + size,
+            instruction_offsets: (first_address..first_address + size).step_by(2).collect(),
+            successors: successors.to_vec(),
+            predecessors: predecessors.to_vec(),
+            exception_successors: exception_successors.to_vec(),
+            exception_predecessors: exception_predecessors.to_vec(),
+        }
+    }
+
+    /// An if/else whose branches join at 0x10, the `then` branch is in a try range handled at
+    /// 0x20, and a dead block at 0x30.
+    fn diamond() -> ControlFlowGraph {
+        ControlFlowGraph::from_addresses(
+            vec![
+                block(0x0, 4, &[0x4, 0x8], &[], &[], &[]),
+                block(0x4, 4, &[0x10], &[0x0], &[0x20], &[]),
+                block(0x8, 8, &[0x10], &[0x0], &[], &[]),
+                block(0x10, 2, &[], &[0x4, 0x8, 0x20], &[], &[]),
+                block(0x20, 2, &[0x10], &[], &[], &[0x4]),
+                block(0x30, 2, &[], &[], &[], &[]),
+            ],
+            0x0,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn builds_blocks_by_index() {
+        let graph = diamond();
+        assert_eq!(graph.entry, 0);
+        assert_eq!(graph.blocks.len(), 6);
+        assert_eq!(graph.blocks[0].successors, vec![1, 2]);
+        assert_eq!(graph.blocks[3].predecessors, vec![1, 2, 4]);
+        assert_eq!(graph.blocks[1].exception_successors, vec![4]);
+        assert_eq!(graph.blocks[2].instruction_offsets, vec![0x8, 0xa, 0xc, 0xe]);
+        assert!(graph.blocks[4].is_handler());
+        assert!(!graph.blocks[1].is_handler());
+    }
+
+    #[test]
+    fn rejects_unknown_addresses() {
+        let dangling = vec![block(0x0, 2, &[0x2], &[], &[], &[])];
+        assert!(ControlFlowGraph::from_addresses(dangling, 0x0).is_err());
+        let entry = vec![block(0x0, 2, &[], &[], &[], &[])];
+        assert!(ControlFlowGraph::from_addresses(entry, 0x4).is_err());
+    }
+
+    #[test]
+    fn queries() {
+        let graph = diamond();
+        assert_eq!(graph.block_at(0x0), Some(0));
+        assert_eq!(graph.block_at(0xe), Some(2));
+        assert_eq!(graph.block_at(0x12), None);
+        assert_eq!(
+            graph.edges(),
+            vec![
+                (0, 1, EdgeKind::Regular),
+                (0, 2, EdgeKind::Regular),
+                (1, 3, EdgeKind::Regular),
+                (1, 4, EdgeKind::Exception),
+                (2, 3, EdgeKind::Regular),
+                (4, 3, EdgeKind::Regular),
+            ]
+        );
+        assert_eq!(graph.reachable_from(graph.entry), vec![true, true, true, true, true, false]);
+        assert_eq!(graph.reachable_from(2), vec![false, false, true, true, false, false]);
+    }
+
+    #[test]
+    fn dot() {
+        let graph = ControlFlowGraph::from_addresses(
+            vec![
+                block(0x0, 2, &[0x2], &[], &[0x4], &[]),
+                block(0x2, 2, &[], &[0x0], &[], &[]),
+                block(0x4, 2, &[], &[], &[], &[0x0]),
+            ],
+            0x0,
+        )
+        .unwrap();
+        assert_eq!(
+            graph.to_dot("La;->\"a\"()V"),
+            "digraph \"La;->\\\"a\\\"()V\" {\n\
+             \x20   node [shape=box];\n\
+             \x20   b0 [label=\"0h-2h\", style=bold];\n\
+             \x20   b1 [label=\"2h-4h\"];\n\
+             \x20   b2 [label=\"4h-6h\"];\n\
+             \x20   b0 -> b1;\n\
+             \x20   b0 -> b2 [style=dashed];\n\
+             }\n"
+        );
+    }
+}
@@ -0,0 +1,403 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Disassembly of single classes and methods, as opposed to `IDexUnit::getDisassembly` which
+//! renders the whole unit, and export of a unit as a directory tree with one file per class.
+
+use crate::jeb::com::pnfsoftware::jeb::core::units::code::android::dex::{
+    AccessFlags, DexAddress, DexValue,
+};
+use crate::jeb::com::pnfsoftware::jeb::core::units::{
+    IDexClass, IDexField, IDexMethod, IDexType, IDexUnit, IInteractiveUnit, IUnit,
+};
+use crate::jeb::*;
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisassemblyStyle {
+    /// The directive layout of smali (`.class`, `.method`, `.registers`, ...). The instructions
+    /// are formatted by JEB, with branch targets as offsets instead of labels, and annotations are
+    /// left out, so the output is meant to be read, not to be assembled by smali.
+    Smali,
+    /// Full signatures in the layout of JEB's disassembly view.
+    Jeb,
+}
+
+impl DisassemblyStyle {
+    /// The extension of the files written by `write_tree`.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DisassemblyStyle::Smali => "smali",
+            DisassemblyStyle::Jeb => "txt",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DisassemblyOptions {
+    pub style: DisassemblyStyle,
+    /// Prefix each instruction with its offset in the method.
+    pub offsets: bool,
+    /// Include the comments set on classes, methods and instructions.
+    pub comments: bool,
+}
+
+impl Default for DisassemblyOptions {
+    fn default() -> Self {
+        DisassemblyOptions {
+            style: DisassemblyStyle::Smali,
+            offsets: false,
+            comments: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MemberKind {
+    Class,
+    Field,
+    Method,
+}
+
+pub fn disassemble_method<'a, U>(
+    unit: &U,
+    method: &dyn IDexMethod,
+    options: &DisassemblyOptions,
+) -> Result<'static, String>
+where
+    U: IDexUnit<'a> + IInteractiveUnit<'a>,
+{
+    let mut output = String::new();
+    let signature = method.getSignature(true)?;
+    let address = method.getAddress()?;
+    if options.comments {
+        write_comment(&mut output, unit.getComment(&address)?, "")?;
+    }
+    let address = DexAddress::parse(&address)?;
+    let flags = flag_keywords(method.getAccessFlags()?, MemberKind::Method);
+    match options.style {
+        DisassemblyStyle::Smali => {
+            writeln!(output, ".method {}{}", flags, member_part(&signature))?;
+            if let Some(registers) = method.getRegisterCount()? {
+                writeln!(output, "    .registers {}", registers)?;
+            }
+        }
+        DisassemblyStyle::Jeb => {
+            writeln!(output, "{}{}", flags, signature)?;
+        }
+    }
+    for instruction in method.getInstructions()? {
+        let offset = instruction.getOffset()?;
+        if options.comments {
+            let comment = unit.getComment(&address.with_offset(offset).to_string())?;
+            write_comment(&mut output, comment, "    ")?;
+        }
+        output.push_str("    ");
+        if options.offsets {
+            write!(output, "{:08X}  ", offset)?;
+        }
+        writeln!(output, "{}", instruction.format(Some(unit as &dyn IUnit))?)?;
+    }
+    if options.style == DisassemblyStyle::Smali {
+        writeln!(output, ".end method")?;
+    }
+    Ok(output)
+}
+
+pub fn disassemble_class<'a, U>(
+    unit: &U,
+    class: &dyn IDexClass,
+    options: &DisassemblyOptions,
+) -> Result<'static, String>
+where
+    U: IDexUnit<'a> + IInteractiveUnit<'a>,
+{
+    let mut output = String::new();
+    let signature = class.getSignature()?;
+    if options.comments {
+        write_comment(&mut output, unit.getComment(&class.getAddress()?)?, "")?;
+    }
+    let flags = flag_keywords(class.getAccessFlags()?, MemberKind::Class);
+    let supertypes = type_signatures(class.getSupertypes()?)?;
+    let interfaces = type_signatures(class.getImplementedInterfaces()?)?;
+    match options.style {
+        DisassemblyStyle::Smali => {
+            writeln!(output, ".class {}{}", flags, signature)?;
+            for supertype in &supertypes {
+                writeln!(output, ".super {}", supertype)?;
+            }
+            if let Some(source_file) = class.getSourceFile(unit)? {
+                writeln!(output, ".source \"{}\"", source_file)?;
+            }
+            for interface in &interfaces {
+                writeln!(output, ".implements {}", interface)?;
+            }
+        }
+        DisassemblyStyle::Jeb => {
+            write!(output, "{}class {}", flags, signature)?;
+            if !supertypes.is_empty() {
+                write!(output, " extends {}", supertypes.join(", "))?;
+            }
+            if !interfaces.is_empty() {
+                write!(output, " implements {}", interfaces.join(", "))?;
+            }
+            writeln!(output)?;
+        }
+    }
+    for field in class.getFields()? {
+        writeln!(output)?;
+        output.push_str(&disassemble_field(unit, field.as_ref(), options)?);
+    }
+    for method in class.getMethods()? {
+        writeln!(output)?;
+        output.push_str(&disassemble_method(unit, method.as_ref(), options)?);
+    }
+    Ok(output)
+}
+
+fn disassemble_field<'a, U>(
+    unit: &U,
+    field: &dyn IDexField,
+    options: &DisassemblyOptions,
+) -> Result<'static, String>
+where
+    U: IDexUnit<'a> + IInteractiveUnit<'a>,
+{
+    let mut output = String::new();
+    let signature = field.getSignature(true)?;
+    if options.comments {
+        write_comment(&mut output, unit.getComment(&field.getAddress()?)?, "")?;
+    }
+    let flags = flag_keywords(field.getAccessFlags()?, MemberKind::Field);
+    match options.style {
+        DisassemblyStyle::Smali => write!(output, ".field {}{}", flags, member_part(&signature))?,
+        DisassemblyStyle::Jeb => write!(output, "{}{}", flags, signature)?,
+    }
+    if let Some(value) = field.getStaticInitializer()? {
+        if let Some(value) = format_value(unit, &value)? {
+            write!(output, " = {}", value)?;
+        }
+    }
+    writeln!(output)?;
+    Ok(output)
+}
+
+/// Writes every internal class of `unit` to `directory`, in subdirectories following the
+/// packages, e.g. `com/example/Main.smali`. Returns the files written.
+///
+/// Obfuscated names are made safe for any filesystem: characters some filesystems reject are
+/// replaced, and a class whose path differs from an earlier one only in case gets a numeric
+/// suffix, e.g. `a/B_2.smali` after `a/b.smali`.
+pub fn write_tree<'a, U>(
+    unit: &U,
+    directory: &Path,
+    options: &DisassemblyOptions,
+) -> Result<'static, Vec<PathBuf>>
+where
+    U: IDexUnit<'a> + IInteractiveUnit<'a>,
+{
+    let mut files = vec![];
+    // the relative paths written so far, in lowercase for case-insensitive filesystems
+    let mut taken = HashSet::new();
+    for class in unit.getClasses()? {
+        if !class.isInternal()? {
+            continue;
+        }
+        let signature = class.getSignature()?;
+        let mut segments: Vec<String> = signature
+            .trim_start_matches('L')
+            .trim_end_matches(';')
+            .split('/')
+            .map(path_segment)
+            .collect();
+        let name = segments.pop().unwrap_or_default();
+        let package = segments.join("/");
+        let mut relative = format!("{}/{}.{}", package, name, options.style.extension());
+        let mut suffix = 2;
+        while !taken.insert(relative.to_lowercase()) {
+            relative = format!("{}/{}_{}.{}", package, name, suffix, options.style.extension());
+            suffix += 1;
+        }
+        let path = directory.join(relative.trim_start_matches('/'));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, disassemble_class(unit, class.as_ref(), options)?)?;
+        files.push(path);
+    }
+    Ok(files)
+}
+
+/// `segment` of a class name as a file or directory name that is valid on common filesystems.
+fn path_segment(segment: &str) -> String {
+    const RESERVED: &[&str] = &[
+        "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+        "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+    ];
+    let mut name: String = segment
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Windows drops trailing dots and spaces, and `.` and `..` are not names
+    if name.ends_with(['.', ' ']) || name.is_empty() {
+        name.push('_');
+    }
+    let stem = name.split('.').next().unwrap_or_default();
+    if RESERVED.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// The smali keywords of `flags`, each followed by a space.
+fn flag_keywords(flags: AccessFlags, kind: MemberKind) -> String {
+    let mut keywords = vec![
+        (AccessFlags::PUBLIC, "public"),
+        (AccessFlags::PRIVATE, "private"),
+        (AccessFlags::PROTECTED, "protected"),
+        (AccessFlags::STATIC, "static"),
+        (AccessFlags::FINAL, "final"),
+    ];
+    // some bits mean different things on fields and methods
+    match kind {
+        MemberKind::Class => {}
+        MemberKind::Field => {
+            keywords.push((AccessFlags::VOLATILE, "volatile"));
+            keywords.push((AccessFlags::TRANSIENT, "transient"));
+        }
+        MemberKind::Method => {
+            keywords.push((AccessFlags::SYNCHRONIZED, "synchronized"));
+            keywords.push((AccessFlags::BRIDGE, "bridge"));
+            keywords.push((AccessFlags::VARARGS, "varargs"));
+            keywords.push((AccessFlags::NATIVE, "native"));
+        }
+    }
+    keywords.extend(vec![
+        (AccessFlags::INTERFACE, "interface"),
+        (AccessFlags::ABSTRACT, "abstract"),
+        (AccessFlags::STRICT, "strictfp"),
+        (AccessFlags::SYNTHETIC, "synthetic"),
+        (AccessFlags::ANNOTATION, "annotation"),
+        (AccessFlags::ENUM, "enum"),
+        (AccessFlags::CONSTRUCTOR, "constructor"),
+        (AccessFlags::DECLARED_SYNCHRONIZED, "declared-synchronized"),
+    ]);
+    keywords
+        .into_iter()
+        .filter(|(flag, _)| flags.contains(*flag))
+        .map(|(_, keyword)| format!("{} ", keyword))
+        .collect()
+}
+
+/// `name(args)return` or `name:type` of a member signature `Lclass;->...`.
+fn member_part(signature: &str) -> &str {
+    match signature.find("->") {
+        Some(arrow) => &signature[arrow + 2..],
+        None => signature,
+    }
+}
+
+/// The literal of a static initial value, `None` for values smali prints as annotations.
+fn format_value(unit: &dyn IDexUnit, value: &DexValue) -> Result<'static, Option<String>> {
+    Ok(Some(match value {
+        DexValue::Byte(value) => format!("{}t", hex(*value as i64)),
+        DexValue::Short(value) => format!("{}s", hex(*value as i64)),
+        DexValue::Char(value) => format!("'\\u{:04x}'", value),
+        DexValue::Int(value) => hex(*value as i64),
+        DexValue::Long(value) => format!("{}L", hex(*value)),
+        DexValue::Float(value) => format!("{:?}f", value),
+        DexValue::Double(value) => format!("{:?}", value),
+        DexValue::Boolean(value) => format!("{}", value),
+        DexValue::StringIndex(index) => format!("{:?}", unit.getString(*index)?.getValue()?),
+        DexValue::TypeIndex(index) => unit.getType(*index)?.getSignature(true)?,
+        DexValue::Null => "null".to_string(),
+        _ => return Ok(None),
+    }))
+}
+
+/// `value` in hexadecimal, with a sign instead of two's complement.
+fn hex(value: i64) -> String {
+    if value < 0 {
+        format!("-{:#x}", -(value as i128))
+    } else {
+        format!("{:#x}", value)
+    }
+}
+
+fn write_comment(
+    output: &mut String,
+    comment: Option<String>,
+    indent: &str,
+) -> Result<'static, ()> {
+    if let Some(comment) = comment {
+        for line in comment.lines() {
+            writeln!(output, "{}# {}", indent, line)?;
+        }
+    }
+    Ok(())
+}
+
+fn type_signatures(types: Vec<Box<dyn IDexType + '_>>) -> Result<'static, Vec<String>> {
+    types.iter().map(|ty| ty.getSignature(true)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_segments() {
+        assert_eq!(path_segment("Main"), "Main");
+        assert_eq!(path_segment("Main$1"), "Main$1");
+        assert_eq!(path_segment("a<b>:c\"d\\e|f?g*h"), "a_b__c_d_e_f_g_h");
+        assert_eq!(path_segment("tab\there"), "tab_here");
+        assert_eq!(path_segment(""), "_");
+        assert_eq!(path_segment("."), "._");
+        assert_eq!(path_segment(".."), ".._");
+        assert_eq!(path_segment("name."), "name._");
+        assert_eq!(path_segment("name "), "name _");
+        assert_eq!(path_segment(".hidden"), ".hidden");
+        assert_eq!(path_segment("con"), "_con");
+        assert_eq!(path_segment("Lpt1.txt"), "_Lpt1.txt");
+        assert_eq!(path_segment("console"), "console");
+    }
+
+    #[test]
+    fn hex_literals() {
+        assert_eq!(hex(0), "0x0");
+        assert_eq!(hex(255), "0xff");
+        assert_eq!(hex(-1), "-0x1");
+        assert_eq!(hex(i64::MIN), "-0x8000000000000000");
+    }
+
+    #[test]
+    fn flag_keyword_order_and_kind() {
+        let flags = AccessFlags::PUBLIC | AccessFlags::STATIC | AccessFlags::FINAL;
+        assert_eq!(flag_keywords(flags, MemberKind::Class), "public static final ");
+        assert_eq!(flag_keywords(AccessFlags::empty(), MemberKind::Method), "");
+        // 0x40 and 0x80 mean volatile/transient on fields, bridge/varargs on methods
+        let flags = AccessFlags::PRIVATE | AccessFlags::VOLATILE | AccessFlags::TRANSIENT;
+        assert_eq!(flag_keywords(flags, MemberKind::Field), "private volatile transient ");
+        assert_eq!(flag_keywords(flags, MemberKind::Method), "private bridge varargs ");
+        assert_eq!(flag_keywords(flags, MemberKind::Class), "private ");
+        let flags = AccessFlags::PUBLIC | AccessFlags::CONSTRUCTOR | AccessFlags::SYNTHETIC;
+        assert_eq!(flag_keywords(flags, MemberKind::Method), "public synthetic constructor ");
+    }
+
+    #[test]
+    fn member_parts() {
+        assert_eq!(member_part("Lcom/example/Foo;->bar(I)V"), "bar(I)V");
+        assert_eq!(member_part("Lcom/example/Foo;->count:I"), "count:I");
+        assert_eq!(member_part("Lcom/example/Foo;"), "Lcom/example/Foo;");
+    }
+}
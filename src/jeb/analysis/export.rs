@@ -0,0 +1,249 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Serialisable snapshots of a dex unit, and a streaming exporter writing them as JSON lines or
+//! MessagePack, so the analysis can be consumed without a JVM. Needs the `serde` feature.
+
+use crate::jeb::analysis::xrefs::{self, Reference};
+use crate::jeb::com::pnfsoftware::jeb::core::units::{
+    IDalvikInstruction, IDexClass, IDexField, IDexMethod, IDexString, IDexType, IDexUnit,
+};
+use crate::jeb::*;
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassSnapshot {
+    pub index: i32,
+    /// The signature with the user renames applied.
+    pub signature: String,
+    pub original_signature: String,
+    pub access_flags: u32,
+    pub supertypes: Vec<String>,
+    pub interfaces: Vec<String>,
+    pub fields: Vec<String>,
+    pub methods: Vec<String>,
+}
+
+impl ClassSnapshot {
+    pub fn new(class: &dyn IDexClass) -> Result<'static, ClassSnapshot> {
+        Ok(ClassSnapshot {
+            index: class.getIndex()?,
+            signature: class.getSignature()?,
+            original_signature: class.getClassType()?.getSignature(false)?,
+            access_flags: class.getAccessFlags()?.bits(),
+            supertypes: type_signatures(class.getSupertypes()?)?,
+            interfaces: type_signatures(class.getImplementedInterfaces()?)?,
+            fields: class
+                .getFields()?
+                .iter()
+                .map(|field| field.getSignature(true))
+                .collect::<Result<_>>()?,
+            methods: class
+                .getMethods()?
+                .iter()
+                .map(|method| method.getSignature(true))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSnapshot {
+    pub index: i32,
+    pub signature: String,
+    pub original_signature: String,
+    pub class: String,
+    pub field_type: String,
+    pub access_flags: u32,
+}
+
+impl FieldSnapshot {
+    pub fn new(field: &dyn IDexField) -> Result<'static, FieldSnapshot> {
+        Ok(FieldSnapshot {
+            index: field.getIndex()?,
+            signature: field.getSignature(true)?,
+            original_signature: field.getSignature(false)?,
+            class: field.getClassType()?.getSignature(true)?,
+            field_type: field.getFieldType()?.getSignature(true)?,
+            access_flags: field.getAccessFlags()?.bits(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstructionSnapshot {
+    pub offset: i64,
+    pub opcode: i32,
+    pub mnemonic: String,
+    /// The instruction as formatted by JEB.
+    pub text: String,
+}
+
+impl InstructionSnapshot {
+    pub fn new(instruction: &dyn IDalvikInstruction) -> Result<'static, InstructionSnapshot> {
+        Ok(InstructionSnapshot {
+            offset: instruction.getOffset()?,
+            opcode: instruction.getOpcode()?.opcode(),
+            mnemonic: instruction.getMnemonic()?,
+            text: instruction.format(None)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodSnapshot {
+    pub index: i32,
+    pub signature: String,
+    pub original_signature: String,
+    pub class: String,
+    pub return_type: String,
+    pub parameter_types: Vec<String>,
+    pub access_flags: u32,
+    /// `None` unless `ExportOptions::instructions` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<Vec<InstructionSnapshot>>,
+    /// The pool items referenced by the code, `None` unless `ExportOptions::references` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<Vec<Reference>>,
+}
+
+impl MethodSnapshot {
+    pub fn new(
+        method: &dyn IDexMethod,
+        options: &ExportOptions,
+    ) -> Result<'static, MethodSnapshot> {
+        let instructions = if options.instructions {
+            Some(
+                method
+                    .getDalvikInstructions()?
+                    .iter()
+                    .map(|instruction| InstructionSnapshot::new(instruction.as_ref()))
+                    .collect::<Result<_>>()?,
+            )
+        } else {
+            None
+        };
+        let references = if options.references {
+            Some(xrefs::references_from(method)?)
+        } else {
+            None
+        };
+        Ok(MethodSnapshot {
+            index: method.getIndex()?,
+            signature: method.getSignature(true)?,
+            original_signature: method.getSignature(false)?,
+            class: method.getClassType()?.getSignature(true)?,
+            return_type: method.getReturnType()?.getSignature(true)?,
+            parameter_types: type_signatures(method.getParameterTypes()?)?,
+            access_flags: method.getAccessFlags()?.bits(),
+            instructions,
+            references,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StringSnapshot {
+    pub index: i32,
+    pub value: String,
+}
+
+impl StringSnapshot {
+    pub fn new(string: &dyn IDexString) -> Result<'static, StringSnapshot> {
+        Ok(StringSnapshot {
+            index: string.getIndex()?,
+            value: string.getValue()?,
+        })
+    }
+}
+
+/// One item of an export, tagged with its kind.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+pub enum Record {
+    Class(ClassSnapshot),
+    Field(FieldSnapshot),
+    Method(MethodSnapshot),
+    String(StringSnapshot),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON object per line.
+    JsonLines,
+    /// A sequence of MessagePack maps.
+    MessagePack,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Include the instructions of each method.
+    pub instructions: bool,
+    /// Include the pool items referenced by each method.
+    pub references: bool,
+    /// Include the string pool.
+    pub strings: bool,
+}
+
+/// Writes the internal classes of `unit`, each followed by its fields and methods, then the
+/// strings if requested. Records are written as they are read, the unit is never held in memory
+/// as a whole. Returns the number of records written.
+pub fn export<W: Write>(
+    unit: &dyn IDexUnit,
+    mut writer: W,
+    format: ExportFormat,
+    options: &ExportOptions,
+) -> Result<'static, usize> {
+    let mut count = 0;
+    for class in unit.getClasses()? {
+        if !class.isInternal()? {
+            continue;
+        }
+        let snapshot = ClassSnapshot::new(class.as_ref())?;
+        write_record(&mut writer, format, &Record::Class(snapshot))?;
+        count += 1;
+        for field in class.getFields()? {
+            let field = FieldSnapshot::new(field.as_ref())?;
+            write_record(&mut writer, format, &Record::Field(field))?;
+            count += 1;
+        }
+        for method in class.getMethods()? {
+            let method = MethodSnapshot::new(method.as_ref(), options)?;
+            write_record(&mut writer, format, &Record::Method(method))?;
+            count += 1;
+        }
+    }
+    if options.strings {
+        for string in unit.getStrings()? {
+            let string = StringSnapshot::new(string.as_ref())?;
+            write_record(&mut writer, format, &Record::String(string))?;
+            count += 1;
+        }
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+pub fn write_record<W: Write>(
+    writer: &mut W,
+    format: ExportFormat,
+    record: &Record,
+) -> Result<'static, ()> {
+    match format {
+        ExportFormat::JsonLines => {
+            serde_json::to_writer(&mut *writer, record)?;
+            writer.write_all(b"\n")?;
+        }
+        ExportFormat::MessagePack => rmp_serde::encode::write_named(writer, record)?,
+    }
+    Ok(())
+}
+
+fn type_signatures(types: Vec<Box<dyn IDexType + '_>>) -> Result<'static, Vec<String>> {
+    types.iter().map(|ty| ty.getSignature(true)).collect()
+}
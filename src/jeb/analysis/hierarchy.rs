@@ -0,0 +1,331 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The class hierarchy of one or more dex units. Types are identified by their original type
+//! signature, e.g. `Lcom/example/Foo;`, methods by their sub-signature, e.g. `bar(I)V`.
+
+use crate::jeb::com::pnfsoftware::jeb::core::units::code::android::dex::AccessFlags;
+use crate::jeb::com::pnfsoftware::jeb::core::units::IDexUnit;
+use crate::jeb::*;
+
+use std::collections::{HashMap, HashSet};
+
+const OBJECT: &str = "Ljava/lang/Object;";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassInfo {
+    pub signature: String,
+    pub superclass: Option<String>,
+    pub interfaces: Vec<String>,
+    pub is_interface: bool,
+    /// The sub-signatures of the declared methods.
+    pub methods: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClassHierarchy {
+    classes: HashMap<String, ClassInfo>,
+    direct_subtypes: HashMap<String, Vec<String>>,
+}
+
+impl ClassHierarchy {
+    pub fn new() -> ClassHierarchy {
+        ClassHierarchy::default()
+    }
+
+    pub fn from_unit(unit: &dyn IDexUnit) -> Result<'static, ClassHierarchy> {
+        let mut hierarchy = ClassHierarchy::new();
+        hierarchy.add_unit(unit)?;
+        Ok(hierarchy)
+    }
+
+    /// Adds the classes defined in `unit`, e.g. for the other dex files of a multi-dex APK.
+    pub fn add_unit(&mut self, unit: &dyn IDexUnit) -> Result<'static, ()> {
+        for class in unit.getClasses()? {
+            let mut methods = HashSet::new();
+            for method in class.getMethods()? {
+                let signature = method.getSignature(false)?;
+                if let Some(arrow) = signature.find("->") {
+                    methods.insert(signature[arrow + 2..].to_string());
+                }
+            }
+            let mut interfaces = vec![];
+            for interface in class.getImplementedInterfaces()? {
+                interfaces.push(interface.getSignature(false)?);
+            }
+            let superclass = match class.getSupertypes()?.first() {
+                Some(supertype) => Some(supertype.getSignature(false)?),
+                None => None,
+            };
+            self.add_class(ClassInfo {
+                signature: class.getClassType()?.getSignature(false)?,
+                superclass: superclass.filter(|superclass| !interfaces.contains(superclass)),
+                interfaces,
+                is_interface: class.getAccessFlags()?.contains(AccessFlags::INTERFACE),
+                methods,
+            });
+        }
+        Ok(())
+    }
+
+    /// Adds or replaces a class, e.g. a stub of a framework class the dex unit only references.
+    pub fn add_class(&mut self, class: ClassInfo) {
+        if let Some(previous) = self.classes.remove(&class.signature) {
+            for supertype in previous.superclass.iter().chain(&previous.interfaces) {
+                if let Some(subtypes) = self.direct_subtypes.get_mut(supertype) {
+                    subtypes.retain(|subtype| subtype != &previous.signature);
+                }
+            }
+        }
+        for supertype in class.superclass.iter().chain(&class.interfaces) {
+            self.direct_subtypes
+                .entry(supertype.clone())
+                .or_default()
+                .push(class.signature.clone());
+        }
+        self.classes.insert(class.signature.clone(), class);
+    }
+
+    pub fn class(&self, signature: &str) -> Option<&ClassInfo> {
+        self.classes.get(signature)
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item = &ClassInfo> {
+        self.classes.values()
+    }
+
+    /// The superclass chain of `signature`, nearest first, as far as the classes are known.
+    pub fn superclasses(&self, signature: &str) -> Vec<String> {
+        let mut superclasses = vec![];
+        let mut current = self.class(signature).and_then(|class| class.superclass.clone());
+        while let Some(superclass) = current {
+            if superclasses.contains(&superclass) {
+                break;
+            }
+            current = self.class(&superclass).and_then(|class| class.superclass.clone());
+            superclasses.push(superclass);
+        }
+        superclasses
+    }
+
+    /// All superclasses and implemented interfaces of `signature`, transitively.
+    pub fn supertypes(&self, signature: &str) -> Vec<String> {
+        self.transitive(signature, |class| {
+            class.superclass.iter().chain(&class.interfaces).cloned().collect()
+        })
+    }
+
+    pub fn direct_subtypes(&self, signature: &str) -> &[String] {
+        self.direct_subtypes
+            .get(signature)
+            .map_or(&[], |subtypes| subtypes.as_slice())
+    }
+
+    /// All classes and interfaces extending or implementing `signature`, transitively.
+    pub fn subtypes(&self, signature: &str) -> Vec<String> {
+        let mut subtypes = vec![];
+        let mut visited = HashSet::new();
+        let mut stack: Vec<&String> = self.direct_subtypes(signature).iter().collect();
+        while let Some(subtype) = stack.pop() {
+            if visited.insert(subtype) {
+                subtypes.push(subtype.clone());
+                stack.extend(self.direct_subtypes(subtype));
+            }
+        }
+        subtypes
+    }
+
+    /// The non-interface classes implementing the interface `signature`, directly or not.
+    pub fn implementors(&self, signature: &str) -> Vec<String> {
+        self.subtypes(signature)
+            .into_iter()
+            .filter(|subtype| self.class(subtype).is_some_and(|class| !class.is_interface))
+            .collect()
+    }
+
+    fn transitive<F>(&self, signature: &str, next: F) -> Vec<String>
+    where
+        F: Fn(&ClassInfo) -> Vec<String>,
+    {
+        let mut result = vec![];
+        let mut visited = HashSet::new();
+        let mut stack = self.class(signature).map(&next).unwrap_or_default();
+        while let Some(current) = stack.pop() {
+            if visited.insert(current.clone()) {
+                if let Some(class) = self.class(&current) {
+                    stack.extend(next(class));
+                }
+                result.push(current);
+            }
+        }
+        result
+    }
+
+    /// Whether a value of type `from` can be assigned to `to`, for class and array signatures.
+    pub fn is_assignable_to(&self, from: &str, to: &str) -> bool {
+        if from == to || to == OBJECT {
+            return true;
+        }
+        if let Some(from_element) = from.strip_prefix('[') {
+            return match to.strip_prefix('[') {
+                Some(to_element) => {
+                    from_element.starts_with(['L', '['])
+                        && self.is_assignable_to(from_element, to_element)
+                }
+                None => to == "Ljava/lang/Cloneable;" || to == "Ljava/io/Serializable;",
+            };
+        }
+        self.supertypes(from).iter().any(|supertype| supertype == to)
+    }
+
+    /// The method a call of `sub_signature` on an instance of `class` runs: the nearest declaration
+    /// along the superclass chain, then a default method of an implemented interface.
+    pub fn resolve_method(&self, class: &str, sub_signature: &str) -> Option<String> {
+        let declares = |class: &str| {
+            self.class(class).is_some_and(|info| info.methods.contains(sub_signature))
+        };
+        let mut chain = vec![class.to_string()];
+        chain.extend(self.superclasses(class));
+        if let Some(declaring) = chain.iter().find(|class| declares(class)) {
+            return Some(format!("{}->{}", declaring, sub_signature));
+        }
+        self.supertypes(class)
+            .into_iter()
+            .find(|supertype| declares(supertype))
+            .map(|declaring| format!("{}->{}", declaring, sub_signature))
+    }
+
+    /// The methods overriding or implementing `method_signature` in subtypes of its class.
+    pub fn overrides(&self, method_signature: &str) -> Vec<String> {
+        let arrow = match method_signature.find("->") {
+            Some(arrow) => arrow,
+            None => return vec![],
+        };
+        let (class, sub_signature) = (&method_signature[..arrow], &method_signature[arrow + 2..]);
+        if sub_signature.starts_with('<') {
+            // constructors and static initializers are never overridden
+            return vec![];
+        }
+        self.subtypes(class)
+            .into_iter()
+            .filter(|subtype| {
+                self.class(subtype).is_some_and(|info| info.methods.contains(sub_signature))
+            })
+            .map(|subtype| format!("{}->{}", subtype, sub_signature))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(signature: &str, superclass: Option<&str>, interfaces: &[&str], methods: &[&str]) -> ClassInfo {
+        ClassInfo {
+            signature: signature.to_string(),
+            superclass: superclass.map(str::to_string),
+            interfaces: interfaces.iter().map(|interface| interface.to_string()).collect(),
+            is_interface: false,
+            methods: methods.iter().map(|method| method.to_string()).collect(),
+        }
+    }
+
+    fn interface(signature: &str, interfaces: &[&str], methods: &[&str]) -> ClassInfo {
+        ClassInfo {
+            is_interface: true,
+            ..class(signature, None, interfaces, methods)
+        }
+    }
+
+    /// `Runnable <- Task <- Job`, `Base implements Task`, `Worker extends Base`, and
+    /// `Other extends Object implements Runnable`.
+    fn hierarchy() -> ClassHierarchy {
+        let mut hierarchy = ClassHierarchy::new();
+        hierarchy.add_class(interface("LRunnable;", &[], &["run()V"]));
+        hierarchy.add_class(interface("LTask;", &["LRunnable;"], &["name()Ljava/lang/String;"]));
+        hierarchy.add_class(interface("LJob;", &["LTask;"], &[]));
+        hierarchy.add_class(class("LBase;", Some(OBJECT), &["LTask;"], &["<init>()V", "run()V"]));
+        hierarchy.add_class(class("LWorker;", Some("LBase;"), &[], &["<init>()V", "run()V", "work()V"]));
+        hierarchy.add_class(class("LOther;", Some(OBJECT), &["LRunnable;"], &["run()V"]));
+        hierarchy
+    }
+
+    fn sorted(mut signatures: Vec<String>) -> Vec<String> {
+        signatures.sort();
+        signatures
+    }
+
+    #[test]
+    fn supertypes_and_subtypes() {
+        let hierarchy = hierarchy();
+        assert_eq!(hierarchy.superclasses("LWorker;"), vec!["LBase;", OBJECT]);
+        assert!(hierarchy.superclasses("LUnknown;").is_empty());
+        assert_eq!(
+            sorted(hierarchy.supertypes("LWorker;")),
+            vec!["LBase;", "LRunnable;", "LTask;", OBJECT]
+        );
+        assert_eq!(hierarchy.direct_subtypes("LTask;"), ["LJob;", "LBase;"]);
+        assert_eq!(
+            sorted(hierarchy.subtypes("LRunnable;")),
+            vec!["LBase;", "LJob;", "LOther;", "LTask;", "LWorker;"]
+        );
+        assert_eq!(
+            sorted(hierarchy.implementors("LRunnable;")),
+            vec!["LBase;", "LOther;", "LWorker;"]
+        );
+    }
+
+    #[test]
+    fn add_class_replaces() {
+        let mut hierarchy = hierarchy();
+        hierarchy.add_class(class("LWorker;", Some("LOther;"), &[], &[]));
+        assert!(hierarchy.direct_subtypes("LBase;").is_empty());
+        assert_eq!(hierarchy.direct_subtypes("LOther;"), ["LWorker;"]);
+        assert_eq!(hierarchy.classes().count(), 6);
+        assert!(hierarchy.class("LWorker;").unwrap().methods.is_empty());
+    }
+
+    #[test]
+    fn cyclic_superclasses_terminate() {
+        let mut hierarchy = ClassHierarchy::new();
+        hierarchy.add_class(class("LA;", Some("LB;"), &[], &[]));
+        hierarchy.add_class(class("LB;", Some("LA;"), &[], &[]));
+        assert_eq!(hierarchy.superclasses("LA;"), vec!["LB;", "LA;"]);
+        assert_eq!(sorted(hierarchy.supertypes("LA;")), vec!["LA;", "LB;"]);
+    }
+
+    #[test]
+    fn assignability() {
+        let hierarchy = hierarchy();
+        assert!(hierarchy.is_assignable_to("LWorker;", "LRunnable;"));
+        assert!(hierarchy.is_assignable_to("LWorker;", OBJECT));
+        assert!(!hierarchy.is_assignable_to("LBase;", "LWorker;"));
+        assert!(!hierarchy.is_assignable_to("LOther;", "LTask;"));
+        assert!(hierarchy.is_assignable_to("[LWorker;", "[LTask;"));
+        assert!(hierarchy.is_assignable_to("[[LWorker;", "[Ljava/lang/Object;"));
+        assert!(hierarchy.is_assignable_to("[I", "Ljava/lang/Cloneable;"));
+        assert!(!hierarchy.is_assignable_to("[I", "[Ljava/lang/Object;"));
+        assert!(!hierarchy.is_assignable_to("[LWorker;", "LTask;"));
+    }
+
+    #[test]
+    fn method_resolution() {
+        let hierarchy = hierarchy();
+        assert_eq!(hierarchy.resolve_method("LWorker;", "run()V").as_deref(), Some("LWorker;->run()V"));
+        assert_eq!(
+            hierarchy.resolve_method("LWorker;", "name()Ljava/lang/String;").as_deref(),
+            Some("LTask;->name()Ljava/lang/String;")
+        );
+        assert_eq!(hierarchy.resolve_method("LWorker;", "missing()V"), None);
+        assert_eq!(
+            sorted(hierarchy.overrides("LRunnable;->run()V")),
+            vec!["LBase;->run()V", "LOther;->run()V", "LWorker;->run()V"]
+        );
+        assert_eq!(hierarchy.overrides("LBase;->run()V"), vec!["LWorker;->run()V"]);
+        assert!(hierarchy.overrides("LBase;-><init>()V").is_empty());
+        assert!(hierarchy.overrides("LBase;").is_empty());
+    }
+}
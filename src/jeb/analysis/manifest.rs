@@ -0,0 +1,83 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The components declared in the `AndroidManifest.xml` of an APK.
+
+use crate::jeb::com::pnfsoftware::jeb::core::units::{IUnit, IXmlUnit, JebXmlUnit};
+use crate::jeb::org::w3c::dom::Document;
+use crate::jeb::*;
+
+use std::convert::TryFrom;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AndroidManifest {
+    pub package: String,
+    /// The class names of the components, e.g. `com.example.MainActivity`.
+    pub activities: Vec<String>,
+    pub services: Vec<String>,
+    pub receivers: Vec<String>,
+    pub providers: Vec<String>,
+    pub application: Option<String>,
+}
+
+impl AndroidManifest {
+    /// Reads the manifest of `apk`, the parent unit of the dex unit(s).
+    pub fn from_apk(apk: &dyn IUnit) -> Result<'static, AndroidManifest> {
+        for child in apk.getChildren()? {
+            if child.getName()? == "Manifest" {
+                let xml_unit = JebXmlUnit::try_from(child.as_ref())?;
+                return AndroidManifest::from_document(&xml_unit.getDocument()?);
+            }
+        }
+        Err("APK has no manifest".into())
+    }
+
+    pub fn from_document(document: &Document) -> Result<'static, AndroidManifest> {
+        let package = document.getDocumentElement()?.getAttribute("package")?;
+        let class_names = |tag: &str| -> Result<'static, Vec<String>> {
+            let elements = document.getElementsByTagName(tag)?;
+            let mut names = vec![];
+            for i in 0..elements.getLength()? {
+                let name = elements.item(i)?.getAttribute("android:name")?;
+                if !name.is_empty() {
+                    names.push(qualify_class_name(&package, &name));
+                }
+            }
+            Ok(names)
+        };
+        Ok(AndroidManifest {
+            activities: class_names("activity")?,
+            services: class_names("service")?,
+            receivers: class_names("receiver")?,
+            providers: class_names("provider")?,
+            application: class_names("application")?.into_iter().next(),
+            package: package.clone(),
+        })
+    }
+
+    /// All component classes as type signatures, e.g. `Lcom/example/MainActivity;`.
+    pub fn component_signatures(&self) -> Vec<String> {
+        self.activities
+            .iter()
+            .chain(&self.services)
+            .chain(&self.receivers)
+            .chain(&self.providers)
+            .chain(&self.application)
+            .map(|name| format!("L{};", name.replace('.', "/")))
+            .collect()
+    }
+}
+
+/// Resolves class names relative to the manifest package, like `.MainActivity`.
+fn qualify_class_name(package: &str, name: &str) -> String {
+    if name.starts_with('.') {
+        format!("{}{}", package, name)
+    } else if !name.contains('.') {
+        format!("{}.{}", package, name)
+    } else {
+        name.to_string()
+    }
+}
@@ -0,0 +1,417 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! ProGuard/R8 `mapping.txt` files. A mapping maps the readable names of the original program to
+//! the obfuscated names found in the dex file:
+//!
+//! ```text
+//! com.example.MainActivity -> a.a:
+//!     java.lang.String title -> a
+//!     void setTitle(java.lang.String) -> a
+//! ```
+
+use crate::jeb::com::pnfsoftware::jeb::core::units::{IDexUnit, IInteractiveUnit};
+use crate::jeb::*;
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberMapping {
+    /// The field type or method return type, as a Java type name.
+    pub type_name: String,
+    pub original: String,
+    /// The argument types of a method, `None` for a field.
+    pub arguments: Option<Vec<String>>,
+    pub obfuscated: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassMapping {
+    /// The fully qualified Java class name, e.g. `com.example.MainActivity`.
+    pub original: String,
+    pub obfuscated: String,
+    pub members: Vec<MemberMapping>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mapping {
+    pub classes: Vec<ClassMapping>,
+}
+
+/// What `Mapping::apply` did.
+#[derive(Debug, Clone, Default)]
+pub struct MappingReport {
+    pub renamed: usize,
+    /// The items JEB refused to rename or move.
+    pub failed: Vec<String>,
+    /// The obfuscated names of the mapping which are not in the dex unit.
+    pub missing: Vec<String>,
+}
+
+/// Converts a Java type name like `java.lang.String[]` to a descriptor like `[Ljava/lang/String;`.
+pub fn java_name_to_descriptor(name: &str) -> String {
+    let mut descriptor = String::new();
+    let mut element = name.trim();
+    while let Some(stripped) = element.strip_suffix("[]") {
+        descriptor.push('[');
+        element = stripped;
+    }
+    let element = match element {
+        "void" => "V".to_string(),
+        "boolean" => "Z".to_string(),
+        "byte" => "B".to_string(),
+        "char" => "C".to_string(),
+        "short" => "S".to_string(),
+        "int" => "I".to_string(),
+        "long" => "J".to_string(),
+        "float" => "F".to_string(),
+        "double" => "D".to_string(),
+        class => format!("L{};", class.replace('.', "/")),
+    };
+    descriptor.push_str(&element);
+    descriptor
+}
+
+/// Converts a descriptor like `[Ljava/lang/String;` to a Java type name like `java.lang.String[]`.
+pub fn descriptor_to_java_name(descriptor: &str) -> String {
+    let dimensions = descriptor.chars().take_while(|c| *c == '[').count();
+    let element = &descriptor[dimensions..];
+    let mut name = match element {
+        "V" => "void".to_string(),
+        "Z" => "boolean".to_string(),
+        "B" => "byte".to_string(),
+        "C" => "char".to_string(),
+        "S" => "short".to_string(),
+        "I" => "int".to_string(),
+        "J" => "long".to_string(),
+        "F" => "float".to_string(),
+        "D" => "double".to_string(),
+        class => class
+            .trim_start_matches('L')
+            .trim_end_matches(';')
+            .replace('/', "."),
+    };
+    for _ in 0..dimensions {
+        name.push_str("[]");
+    }
+    name
+}
+
+/// The simple name of a class name, without package and outer classes.
+fn simple_name(class_name: &str) -> &str {
+    let start = class_name.rfind(['.', '$']).map_or(0, |i| i + 1);
+    &class_name[start..]
+}
+
+fn package_name(class_name: &str) -> &str {
+    class_name.rfind('.').map_or("", |i| &class_name[..i])
+}
+
+impl Mapping {
+    pub fn parse(text: &str) -> Result<'static, Mapping> {
+        let mut mapping = Mapping::default();
+        for (number, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let invalid = || format!("invalid mapping line {}: {}", number + 1, line);
+            let arrow = trimmed.find(" -> ").ok_or_else(invalid)?;
+            let (left, right) = (&trimmed[..arrow], trimmed[arrow + 4..].trim());
+            if !line.starts_with(char::is_whitespace) {
+                mapping.classes.push(ClassMapping {
+                    original: left.trim().to_string(),
+                    obfuscated: right.trim_end_matches(':').to_string(),
+                    members: vec![],
+                });
+                continue;
+            }
+            // strip the line numbers of `1:5:void run() -> a`
+            let left = left.trim_start_matches(|c: char| c.is_ascii_digit() || c == ':');
+            let space = left.find(' ').ok_or_else(invalid)?;
+            let (type_name, name) = (&left[..space], left[space + 1..].trim());
+            let (original, arguments) = match name.find('(') {
+                Some(paren) => {
+                    // R8 puts the original line range after the arguments, `void run():10:14 -> a`
+                    let close = name[paren..].find(')').ok_or_else(invalid)? + paren;
+                    let arguments = name[paren + 1..close]
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|argument| !argument.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                    (&name[..paren], Some(arguments))
+                }
+                None => (name, None),
+            };
+            // `original` may carry the declaring class for inlined methods, `com.Foo.bar`
+            let class = mapping.classes.last_mut().ok_or_else(invalid)?;
+            class.members.push(MemberMapping {
+                type_name: type_name.to_string(),
+                original: original.to_string(),
+                arguments,
+                obfuscated: right.to_string(),
+            });
+        }
+        Ok(mapping)
+    }
+
+    /// The user renames of `unit` as a mapping, from the names given in JEB to the names of the
+    /// dex file.
+    pub fn from_unit(unit: &dyn IDexUnit) -> Result<'static, Mapping> {
+        let mut mapping = Mapping::default();
+        for class in unit.getClasses()? {
+            let class_type = class.getClassType()?;
+            let original = descriptor_to_java_name(&class_type.getSignature(true)?);
+            let obfuscated = descriptor_to_java_name(&class_type.getSignature(false)?);
+            let mut members = vec![];
+            for field in class.getFields()? {
+                let (name, dex_name) = (field.getName(true)?, field.getName(false)?);
+                if name != dex_name {
+                    members.push(MemberMapping {
+                        type_name: descriptor_to_java_name(&field.getFieldType()?.getSignature(true)?),
+                        original: name,
+                        arguments: None,
+                        obfuscated: dex_name,
+                    });
+                }
+            }
+            for method in class.getMethods()? {
+                let (name, dex_name) = (method.getName(true)?, method.getName(false)?);
+                if name != dex_name {
+                    let mut arguments = vec![];
+                    for parameter in method.getParameterTypes()? {
+                        arguments.push(descriptor_to_java_name(&parameter.getSignature(true)?));
+                    }
+                    members.push(MemberMapping {
+                        type_name: descriptor_to_java_name(&method.getReturnType()?.getSignature(true)?),
+                        original: name,
+                        arguments: Some(arguments),
+                        obfuscated: dex_name,
+                    });
+                }
+            }
+            if original != obfuscated || !members.is_empty() {
+                mapping.classes.push(ClassMapping {
+                    original,
+                    obfuscated,
+                    members,
+                });
+            }
+        }
+        Ok(mapping)
+    }
+
+    /// Renames the classes, fields and methods of `unit` to their original names. Classes whose
+    /// original package differs are moved there.
+    pub fn apply<'a, U>(&self, unit: &U) -> Result<'static, MappingReport>
+    where
+        U: IDexUnit<'a> + IInteractiveUnit<'a>,
+    {
+        let mut report = MappingReport::default();
+        // argument types of the mapping are original names, the dex file has the obfuscated ones
+        let obfuscated_names: HashMap<&str, &str> = self
+            .classes
+            .iter()
+            .map(|class| (class.original.as_str(), class.obfuscated.as_str()))
+            .collect();
+        let to_dex_descriptor = |type_name: &str| {
+            let dimensions = type_name.matches("[]").count();
+            let element = type_name.trim_end_matches("[]");
+            let element = obfuscated_names.get(element).copied().unwrap_or(element);
+            java_name_to_descriptor(&format!("{}{}", element, "[]".repeat(dimensions)))
+        };
+
+        let classes = unit.getClasses()?;
+        let mut by_signature = HashMap::new();
+        for (index, class) in classes.iter().enumerate() {
+            by_signature.insert(class.getClassType()?.getSignature(false)?, index);
+        }
+
+        for class_mapping in &self.classes {
+            let class = match by_signature.get(&java_name_to_descriptor(&class_mapping.obfuscated)) {
+                Some(index) => &classes[*index],
+                None => {
+                    report.missing.push(class_mapping.obfuscated.clone());
+                    continue;
+                }
+            };
+            let fields = class.getFields()?;
+            let methods = class.getMethods()?;
+            for member in &class_mapping.members {
+                if member.original.contains('.') || member.original == member.obfuscated {
+                    // inlined from another class, or not renamed
+                    continue;
+                }
+                let description = format!("{}.{}", class_mapping.obfuscated, member.obfuscated);
+                let renamed = match &member.arguments {
+                    None => {
+                        // fields may be overloaded on their type after obfuscation
+                        let field_type = to_dex_descriptor(&member.type_name);
+                        let mut field = None;
+                        for candidate in &fields {
+                            if candidate.getName(false)? == member.obfuscated
+                                && candidate.getFieldType()?.getSignature(false)? == field_type
+                            {
+                                field = Some(candidate);
+                                break;
+                            }
+                        }
+                        match field {
+                            Some(field) => Some(unit.renameField(field.as_ref(), &member.original)?),
+                            None => None,
+                        }
+                    }
+                    Some(arguments) => {
+                        let parameters: String = arguments.iter().map(|argument| to_dex_descriptor(argument)).collect();
+                        let sub_signature = format!(
+                            "{}({}){}",
+                            member.obfuscated,
+                            parameters,
+                            to_dex_descriptor(&member.type_name)
+                        );
+                        let mut method = None;
+                        for candidate in &methods {
+                            let signature = candidate.getSignature(false)?;
+                            let sub = signature.find("->").map_or("", |arrow| &signature[arrow + 2..]);
+                            if sub == sub_signature {
+                                method = Some(candidate);
+                                break;
+                            }
+                        }
+                        match method {
+                            Some(method) => Some(unit.renameMethod(method.as_ref(), &member.original)?),
+                            None => None,
+                        }
+                    }
+                };
+                match renamed {
+                    Some(true) => report.renamed += 1,
+                    Some(false) => report.failed.push(description),
+                    None => report.missing.push(description),
+                }
+            }
+
+            let (original_name, obfuscated_name) =
+                (simple_name(&class_mapping.original), simple_name(&class_mapping.obfuscated));
+            if original_name != obfuscated_name {
+                if unit.renameClass(class.as_ref(), original_name)? {
+                    report.renamed += 1;
+                } else {
+                    report.failed.push(class_mapping.obfuscated.clone());
+                }
+            }
+            let original_package = package_name(&class_mapping.original);
+            if original_package != package_name(&class_mapping.obfuscated)
+                && !unit.moveClassToPackage(class.as_ref(), original_package)?
+            {
+                report.failed.push(class_mapping.obfuscated.clone());
+            }
+        }
+        Ok(report)
+    }
+}
+
+impl fmt::Display for Mapping {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for class in &self.classes {
+            writeln!(f, "{} -> {}:", class.original, class.obfuscated)?;
+            for member in &class.members {
+                match &member.arguments {
+                    Some(arguments) => writeln!(
+                        f,
+                        "    {} {}({}) -> {}",
+                        member.type_name,
+                        member.original,
+                        arguments.join(","),
+                        member.obfuscated
+                    )?,
+                    None => writeln!(
+                        f,
+                        "    {} {} -> {}",
+                        member.type_name, member.original, member.obfuscated
+                    )?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_classes_and_members() {
+        let mapping = Mapping::parse(
+            "# compiler: R8\n\
+             com.example.MainActivity -> a.a:\n\
+             \x20   java.lang.String title -> a\n\
+             \x20   1:5:void setTitle(java.lang.String,int[]) -> a\n\
+             \x20   void run():10:14 -> b\n\
+             \x20   2:3:int com.example.Util.max(int,int):7:8 -> c\n",
+        )
+        .unwrap();
+        assert_eq!(mapping.classes.len(), 1);
+        let class = &mapping.classes[0];
+        assert_eq!(class.original, "com.example.MainActivity");
+        assert_eq!(class.obfuscated, "a.a");
+        assert_eq!(
+            class.members,
+            vec![
+                MemberMapping {
+                    type_name: "java.lang.String".to_string(),
+                    original: "title".to_string(),
+                    arguments: None,
+                    obfuscated: "a".to_string(),
+                },
+                MemberMapping {
+                    type_name: "void".to_string(),
+                    original: "setTitle".to_string(),
+                    arguments: Some(vec!["java.lang.String".to_string(), "int[]".to_string()]),
+                    obfuscated: "a".to_string(),
+                },
+                MemberMapping {
+                    type_name: "void".to_string(),
+                    original: "run".to_string(),
+                    arguments: Some(vec![]),
+                    obfuscated: "b".to_string(),
+                },
+                MemberMapping {
+                    type_name: "int".to_string(),
+                    original: "com.example.Util.max".to_string(),
+                    arguments: Some(vec!["int".to_string(), "int".to_string()]),
+                    obfuscated: "c".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_invalid_lines() {
+        assert!(Mapping::parse("com.example.Foo a.a:\n").is_err());
+        assert!(Mapping::parse("    int count -> a\n").is_err());
+        assert!(Mapping::parse("a -> b:\n    void run( -> a\n").is_err());
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let text = "com.example.Foo -> a.a:\n    int count -> a\n    void add(int,java.lang.String[]) -> b\n";
+        let mapping = Mapping::parse(text).unwrap();
+        assert_eq!(mapping.to_string(), text);
+        assert_eq!(Mapping::parse(&mapping.to_string()).unwrap(), mapping);
+    }
+
+    #[test]
+    fn descriptors() {
+        assert_eq!(java_name_to_descriptor("java.lang.String[][]"), "[[Ljava/lang/String;");
+        assert_eq!(java_name_to_descriptor("int"), "I");
+        assert_eq!(descriptor_to_java_name("[J"), "long[]");
+        assert_eq!(descriptor_to_java_name("La/b/C$D;"), "a.b.C$D");
+    }
+}
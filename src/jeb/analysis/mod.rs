@@ -0,0 +1,52 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Analyses built in Rust on top of the JEB bindings. The results are plain Rust data and do not
+//! hold any reference into the JVM.
+
+pub mod callgraph;
+pub mod cfg;
+pub mod disassembly;
+#[cfg(feature = "serde")]
+pub mod export;
+pub mod hierarchy;
+pub mod manifest;
+pub mod mapping;
+pub mod multidex;
+pub mod strings;
+pub mod xrefs;
+
+/// `value` as a JSON string literal.
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_string;
+
+    #[test]
+    fn json_string_escapes() {
+        assert_eq!(json_string("Lcom/example/Foo;"), "\"Lcom/example/Foo;\"");
+        assert_eq!(json_string("a\"b\\c"), "\"a\\\"b\\\\c\"");
+        assert_eq!(json_string("\n\r\t\u{1}"), "\"\\n\\r\\t\\u0001\"");
+        assert_eq!(json_string("\u{e9}\u{1f600}"), "\"\u{e9}\u{1f600}\"");
+    }
+}
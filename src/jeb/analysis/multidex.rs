@@ -0,0 +1,152 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The dex units of an APK. Depending on its settings JEB either merges `classes.dex` to
+//! `classesN.dex` into a single unit or creates one unit per file; `MultiDex` hides the difference.
+
+use crate::jeb::com::pnfsoftware::jeb::core::units::{
+    IDexClass, IDexMethod, IDexUnit, IUnit, JebDexUnit,
+};
+use crate::jeb::*;
+
+/// Where a class is defined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DexOrigin {
+    /// The index of the unit in `MultiDex::units`.
+    pub unit_index: usize,
+    /// The name of the dex file, e.g. `classes2.dex`. For a unit with a single dex file this is
+    /// the unit name. JEB does not keep the file names of merged units, so for those the name is
+    /// derived from the dex file index following the APK convention, see `dex_file_name`; it is
+    /// a guess for containers that name their dex files differently.
+    pub dex_file: String,
+}
+
+pub struct DexClassEntry<'a> {
+    pub origin: DexOrigin,
+    pub class: Box<dyn IDexClass<'a> + 'a>,
+}
+
+pub struct MultiDex<'a> {
+    units: Vec<JebDexUnit<'a>>,
+}
+
+/// The conventional name of the dex file at `index` of an APK: `classes.dex`, `classes2.dex`, ...
+/// This assumes JEB indexed the files in the order of their names, which is how it loads APKs.
+fn dex_file_name(index: i32) -> String {
+    if index <= 0 {
+        "classes.dex".to_string()
+    } else {
+        format!("classes{}.dex", index + 1)
+    }
+}
+
+impl<'a> MultiDex<'a> {
+    /// Collects all dex units below `root`, e.g. the APK unit.
+    pub fn find(root: &dyn IUnit<'a>) -> Result<'a, MultiDex<'a>> {
+        let mut units = vec![];
+        for entry in root.walk()?.filter_format_type("dex") {
+            let (_, unit) = entry?;
+            units.push(JebDexUnit(
+                jni::objects::JObject::from(unit.get_obj()?.into_inner()).into(),
+            ));
+        }
+        if units.is_empty() {
+            return Err("no dex unit found".into());
+        }
+        Ok(MultiDex { units })
+    }
+
+    pub fn units(&self) -> &[JebDexUnit<'a>] {
+        &self.units
+    }
+
+    /// Whether JEB merged several dex files into one unit.
+    pub fn is_merged(&self) -> Result<'a, bool> {
+        for unit in &self.units {
+            if unit.getDexFileCount()? > 1 {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// The number of dex files over all units.
+    pub fn dex_file_count(&self) -> Result<'a, usize> {
+        let mut count = 0;
+        for unit in &self.units {
+            count += unit.getDexFileCount()?.max(1) as usize;
+        }
+        Ok(count)
+    }
+
+    fn origin(&self, unit_index: usize, class: &dyn IDexClass) -> Result<'a, DexOrigin> {
+        let unit = &self.units[unit_index];
+        let dex_file = if unit.getDexFileCount()? > 1 {
+            dex_file_name(class.getDexFileIndex()?)
+        } else {
+            unit.getName()?
+        };
+        Ok(DexOrigin {
+            unit_index,
+            dex_file,
+        })
+    }
+
+    /// The classes of all dex units, with the dex file each one comes from.
+    pub fn classes(&self) -> Result<'a, Vec<DexClassEntry<'_>>> {
+        let mut classes = vec![];
+        for (unit_index, unit) in self.units.iter().enumerate() {
+            for class in unit.getClasses()? {
+                classes.push(DexClassEntry {
+                    origin: self.origin(unit_index, class.as_ref())?,
+                    class,
+                });
+            }
+        }
+        Ok(classes)
+    }
+
+    /// The class with the type signature `signature`, e.g. `Lcom/example/Foo;`, from any unit.
+    pub fn find_class(&self, signature: &str) -> Result<'a, Option<DexClassEntry<'_>>> {
+        for (unit_index, unit) in self.units.iter().enumerate() {
+            if let Some(class) = unit.getClassByName(signature)? {
+                return Ok(Some(DexClassEntry {
+                    origin: self.origin(unit_index, class.as_ref())?,
+                    class,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The internal method with the signature `signature` from any unit. Units only referencing
+    /// the method are skipped.
+    pub fn find_method(&self, signature: &str) -> Result<'a, Option<Box<dyn IDexMethod + '_>>> {
+        let mut external = None;
+        for unit in &self.units {
+            if let Some(method) = unit.getMethodByName(signature)? {
+                if method.isInternal()? {
+                    return Ok(Some(method));
+                }
+                external.get_or_insert(method);
+            }
+        }
+        Ok(external)
+    }
+
+    /// All internal methods of all units.
+    pub fn methods(&self) -> Result<'a, Vec<Box<dyn IDexMethod + '_>>> {
+        let mut methods = vec![];
+        for unit in &self.units {
+            for method in unit.getMethods()? {
+                if method.isInternal()? {
+                    methods.push(method);
+                }
+            }
+        }
+        Ok(methods)
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Search over the string pool of a dex unit, and extraction of the constants in method bodies.
+
+use crate::jeb::analysis::xrefs::{self, Xref};
+use crate::jeb::com::pnfsoftware::jeb::core::units::code::android::dex::{
+    DalvikOpcode, DalvikOperand, DexPoolType,
+};
+use crate::jeb::com::pnfsoftware::jeb::core::units::{IDexMethod, IDexUnit};
+use crate::jeb::*;
+
+use regex::Regex;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringMatch {
+    /// The index in the string pool.
+    pub index: i32,
+    pub value: String,
+}
+
+impl StringMatch {
+    /// The code referencing the string.
+    pub fn xrefs(&self, unit: &dyn IDexUnit) -> Result<'static, Vec<Xref>> {
+        xrefs::xrefs_to_index(unit, DexPoolType::String, self.index)
+    }
+}
+
+/// A sequence of three characters, the unit of the substring index.
+type Trigram = (char, char, char);
+
+/// `value` lowercased character by character. Unlike `str::to_lowercase` the result does not
+/// depend on the surrounding characters, so a substring of `value` folds to a substring of
+/// `fold(value)`.
+fn fold(value: &str) -> String {
+    value.chars().flat_map(char::to_lowercase).collect()
+}
+
+fn trigrams(folded: &str) -> impl Iterator<Item = Trigram> + '_ {
+    let chars: Vec<char> = folded.chars().collect();
+    (0..chars.len().saturating_sub(2)).map(move |i| (chars[i], chars[i + 1], chars[i + 2]))
+}
+
+/// The strings of a dex unit, read once from the JVM. Strings are decoded from the dex MUTF-8
+/// encoding, so matching works on Unicode characters, not on bytes.
+///
+/// Substring search goes through a trigram index over the case-folded strings, so only strings
+/// containing every trigram of the needle are compared. Needles shorter than three characters
+/// and regular expressions are matched against every string.
+#[derive(Debug, Clone, Default)]
+pub struct StringIndex {
+    strings: Vec<StringMatch>,
+    /// The case-folded strings, for case insensitive search.
+    folded: Vec<String>,
+    /// The position in `strings` of each pool index.
+    positions: HashMap<i32, usize>,
+    /// The positions in `strings` of the strings containing each trigram, ascending.
+    trigrams: HashMap<Trigram, Vec<usize>>,
+}
+
+impl StringIndex {
+    pub fn new(unit: &dyn IDexUnit) -> Result<'static, StringIndex> {
+        let mut strings = vec![];
+        for string in unit.getStrings()? {
+            strings.push(StringMatch {
+                index: string.getIndex()?,
+                value: string.getValue()?,
+            });
+        }
+        Ok(StringIndex::from_strings(strings))
+    }
+
+    fn from_strings(strings: Vec<StringMatch>) -> StringIndex {
+        let folded: Vec<String> = strings.iter().map(|string| fold(&string.value)).collect();
+        let positions = strings
+            .iter()
+            .enumerate()
+            .map(|(position, string)| (string.index, position))
+            .collect();
+        let mut trigrams: HashMap<Trigram, Vec<usize>> = HashMap::new();
+        for (position, value) in folded.iter().enumerate() {
+            for trigram in self::trigrams(value) {
+                let postings = trigrams.entry(trigram).or_default();
+                if postings.last() != Some(&position) {
+                    postings.push(position);
+                }
+            }
+        }
+        StringIndex {
+            strings,
+            folded,
+            positions,
+            trigrams,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    pub fn get(&self, index: i32) -> Option<&StringMatch> {
+        self.positions.get(&index).map(|position| &self.strings[*position])
+    }
+
+    /// The positions of the strings which may contain `folded_needle`, `None` if the needle is
+    /// too short for the index.
+    fn candidates(&self, folded_needle: &str) -> Option<Vec<usize>> {
+        let mut postings = vec![];
+        for trigram in trigrams(folded_needle) {
+            match self.trigrams.get(&trigram) {
+                Some(positions) => postings.push(positions),
+                None => return Some(vec![]),
+            }
+        }
+        postings.sort_by_key(|positions| positions.len());
+        let (shortest, others) = postings.split_first()?;
+        Some(
+            shortest
+                .iter()
+                .copied()
+                .filter(|position| {
+                    others
+                        .iter()
+                        .all(|positions| positions.binary_search(position).is_ok())
+                })
+                .collect(),
+        )
+    }
+
+    pub fn find(&self, needle: &str, case_insensitive: bool) -> Vec<&StringMatch> {
+        let folded_needle = fold(needle);
+        let matches = |position: &usize| {
+            if case_insensitive {
+                self.folded[*position].contains(&folded_needle)
+            } else {
+                self.strings[*position].value.contains(needle)
+            }
+        };
+        let candidates = self
+            .candidates(&folded_needle)
+            .unwrap_or_else(|| (0..self.strings.len()).collect());
+        candidates
+            .into_iter()
+            .filter(matches)
+            .map(|position| &self.strings[position])
+            .collect()
+    }
+
+    /// The strings matching `regex`, this is a scan over all strings.
+    pub fn find_regex(&self, regex: &Regex) -> Vec<&StringMatch> {
+        self.strings
+            .iter()
+            .filter(|string| regex.is_match(&string.value))
+            .collect()
+    }
+
+    /// The matches of `needle` together with the code using each of them.
+    pub fn find_with_xrefs(
+        &self,
+        unit: &dyn IDexUnit,
+        needle: &str,
+        case_insensitive: bool,
+    ) -> Result<'static, Vec<(&StringMatch, Vec<Xref>)>> {
+        let mut results = vec![];
+        for string in self.find(needle, case_insensitive) {
+            results.push((string, string.xrefs(unit)?));
+        }
+        Ok(results)
+    }
+}
+
+/// A literal of a method body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumericConstant {
+    pub offset: i64,
+    pub opcode: DalvikOpcode,
+    pub value: i64,
+}
+
+/// The data of a `fill-array-data`, e.g. a `byte[]` initialiser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrayInitializer {
+    pub offset: i64,
+    /// The size in bytes of one element, 1 for `byte[]`.
+    pub element_width: i32,
+    /// The elements concatenated, little endian like in the dex file.
+    pub data: Vec<u8>,
+}
+
+impl ArrayInitializer {
+    pub fn len(&self) -> usize {
+        if self.element_width > 0 {
+            self.data.len() / self.element_width as usize
+        } else {
+            0
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The numeric literals of `method`: `const*` values and the literals of `*/lit8`, `*/lit16`.
+pub fn numeric_constants(method: &dyn IDexMethod) -> Result<'static, Vec<NumericConstant>> {
+    let mut constants = vec![];
+    for instruction in method.getDalvikInstructions()? {
+        for operand in instruction.getOperands()? {
+            if let DalvikOperand::Immediate(value) = operand {
+                constants.push(NumericConstant {
+                    offset: instruction.getOffset()?,
+                    opcode: instruction.getOpcode()?,
+                    value,
+                });
+            }
+        }
+    }
+    Ok(constants)
+}
+
+/// The array initialisers of `method`.
+pub fn array_initializers(method: &dyn IDexMethod) -> Result<'static, Vec<ArrayInitializer>> {
+    let mut initializers = vec![];
+    for instruction in method.getDalvikInstructions()? {
+        if instruction.getOpcode()? != DalvikOpcode::FillArrayData {
+            continue;
+        }
+        if let Some(array_data) = instruction.getArrayData()? {
+            initializers.push(ArrayInitializer {
+                offset: instruction.getOffset()?,
+                element_width: array_data.element_width,
+                data: array_data.elements.concat(),
+            });
+        }
+    }
+    Ok(initializers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(values: &[&str]) -> StringIndex {
+        StringIndex::from_strings(
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| StringMatch {
+                    index: index as i32 * 2,
+                    value: value.to_string(),
+                })
+                .collect(),
+        )
+    }
+
+    fn values<'s>(matches: Vec<&'s StringMatch>) -> Vec<&'s str> {
+        matches.into_iter().map(|string| string.value.as_str()).collect()
+    }
+
+    #[test]
+    fn get_by_pool_index() {
+        let index = index(&["a", "b", "c"]);
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.get(4).map(|string| string.value.as_str()), Some("c"));
+        assert!(index.get(1).is_none());
+        assert!(StringIndex::default().is_empty());
+    }
+
+    #[test]
+    fn find_substrings() {
+        let index = index(&["https://example.com", "HTTP/1.1", "http", "ftp", "xhttpx", ""]);
+        assert_eq!(values(index.find("http", false)), vec!["https://example.com", "http", "xhttpx"]);
+        assert_eq!(
+            values(index.find("HTTP", true)),
+            vec!["https://example.com", "HTTP/1.1", "http", "xhttpx"]
+        );
+        assert_eq!(values(index.find("tp", false)), vec!["https://example.com", "http", "ftp", "xhttpx"]);
+        assert_eq!(index.find("", false).len(), 6);
+        assert!(index.find("gopher", true).is_empty());
+        assert!(index.find("tps:/x", false).is_empty());
+    }
+
+    #[test]
+    fn find_non_ascii() {
+        let index = index(&["ΑΣΒΓ", "Straße", "日本語テキスト"]);
+        assert_eq!(values(index.find("σβγ", true)), vec!["ΑΣΒΓ"]);
+        assert!(index.find("σβγ", false).is_empty());
+        assert_eq!(values(index.find("STRAßE", true)), vec!["Straße"]);
+        assert_eq!(values(index.find("本語テ", false)), vec!["日本語テキスト"]);
+    }
+
+    #[test]
+    fn find_by_regex() {
+        let index = index(&["key=1234", "key=abcd", "1234"]);
+        let regex = Regex::new(r"^key=\d+$").unwrap();
+        assert_eq!(values(index.find_regex(&regex)), vec!["key=1234"]);
+    }
+
+    #[test]
+    fn array_initializer_len() {
+        let array = |element_width, data: &[u8]| ArrayInitializer {
+            offset: 0,
+            element_width,
+            data: data.to_vec(),
+        };
+        assert_eq!(array(1, &[1, 2, 3]).len(), 3);
+        assert_eq!(array(4, &[0; 8]).len(), 2);
+        assert!(array(2, &[]).is_empty());
+        assert!(array(0, &[1, 2]).is_empty());
+    }
+}
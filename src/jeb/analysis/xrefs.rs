@@ -0,0 +1,188 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Typed cross-references between dex methods and the items of the dex pools. References to an
+//! item come from JEB's reference manager, references from a method are read off its instructions.
+
+use crate::jeb::com::pnfsoftware::jeb::core::units::code::android::dex::{
+    DalvikOpcode, DalvikOperand, DexAddress, DexPoolType,
+};
+use crate::jeb::com::pnfsoftware::jeb::core::units::{
+    IDexClass, IDexField, IDexMethod, IDexString, IDexType, IDexUnit,
+};
+use crate::jeb::*;
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum XrefKind {
+    Invoke,
+    Read,
+    Write,
+    /// Any other use, e.g. `const-string`, `new-instance` or `check-cast`.
+    Reference,
+}
+
+impl XrefKind {
+    pub fn from_opcode(opcode: DalvikOpcode) -> XrefKind {
+        use DalvikOpcode::*;
+        match opcode {
+            Iget | IgetWide | IgetObject | IgetBoolean | IgetByte | IgetChar | IgetShort | Sget
+            | SgetWide | SgetObject | SgetBoolean | SgetByte | SgetChar | SgetShort => {
+                XrefKind::Read
+            }
+            Iput | IputWide | IputObject | IputBoolean | IputByte | IputChar | IputShort | Sput
+            | SputWide | SputObject | SputBoolean | SputByte | SputChar | SputShort => {
+                XrefKind::Write
+            }
+            opcode if opcode.is_invoke() => XrefKind::Invoke,
+            _ => XrefKind::Reference,
+        }
+    }
+}
+
+/// A reference to a pool item from the code of a method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xref {
+    /// The signature of the referencing method.
+    pub method_signature: String,
+    /// The offset of the referencing instruction, `None` if JEB reports no offset.
+    pub offset: Option<i64>,
+    pub kind: XrefKind,
+}
+
+impl Xref {
+    /// The referencing method, `None` if `unit` does not define it.
+    pub fn method<'u>(
+        &self,
+        unit: &'u dyn IDexUnit,
+    ) -> Result<'static, Option<Box<dyn IDexMethod<'u> + 'u>>> {
+        unit.getMethodByName(&self.method_signature)
+    }
+}
+
+/// A reference from the code of a method to a pool item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reference {
+    pub offset: i64,
+    pub kind: XrefKind,
+    pub pool_type: DexPoolType,
+    pub index: i32,
+}
+
+/// A dex item which can be the target of a cross-reference.
+pub trait XrefTarget {
+    fn pool_reference(&self) -> Result<'static, (DexPoolType, i32)>;
+}
+
+impl<'a> XrefTarget for dyn IDexMethod<'a> + 'a {
+    fn pool_reference(&self) -> Result<'static, (DexPoolType, i32)> {
+        Ok((DexPoolType::Method, self.getIndex()?))
+    }
+}
+
+impl<'a> XrefTarget for dyn IDexField<'a> + 'a {
+    fn pool_reference(&self) -> Result<'static, (DexPoolType, i32)> {
+        Ok((DexPoolType::Field, self.getIndex()?))
+    }
+}
+
+impl<'a> XrefTarget for dyn IDexString<'a> + 'a {
+    fn pool_reference(&self) -> Result<'static, (DexPoolType, i32)> {
+        Ok((DexPoolType::String, self.getIndex()?))
+    }
+}
+
+impl<'a> XrefTarget for dyn IDexType<'a> + 'a {
+    fn pool_reference(&self) -> Result<'static, (DexPoolType, i32)> {
+        Ok((DexPoolType::Type, self.getIndex()?))
+    }
+}
+
+/// Classes are referenced through their type.
+impl<'a> XrefTarget for dyn IDexClass<'a> + 'a {
+    fn pool_reference(&self) -> Result<'static, (DexPoolType, i32)> {
+        Ok((DexPoolType::Type, self.getClassType()?.getIndex()?))
+    }
+}
+
+/// The references to `target` from the code of `unit`.
+pub fn xrefs_to<T: XrefTarget + ?Sized>(unit: &dyn IDexUnit, target: &T) -> Result<'static, Vec<Xref>> {
+    let (pool_type, index) = target.pool_reference()?;
+    xrefs_to_index(unit, pool_type, index)
+}
+
+/// The references to the item `index` of the pool `pool_type`. References whose address is not a
+/// dex address are skipped.
+pub fn xrefs_to_index(
+    unit: &dyn IDexUnit,
+    pool_type: DexPoolType,
+    index: i32,
+) -> Result<'static, Vec<Xref>> {
+    let reference_manager = unit.getReferenceManager()?;
+    // the opcodes per offset of the referencing methods, to tell the kind of each reference
+    let mut opcodes: HashMap<String, HashMap<i64, DalvikOpcode>> = HashMap::new();
+    let mut xrefs = vec![];
+    for address in reference_manager.getReferences(&pool_type, index)? {
+        let address = match DexAddress::parse(&address.getInternalAddress()?) {
+            Ok(address) => address,
+            // e.g. the address of a native method or of an item JEB synthesised
+            Err(_) => continue,
+        };
+        let method_signature = address.member_signature().unwrap_or_else(|| address.class.clone());
+        let method_signature = method_signature.as_str();
+        let offset = address.offset;
+        let kind = match offset {
+            Some(offset) => {
+                if !opcodes.contains_key(method_signature) {
+                    let mut method_opcodes = HashMap::new();
+                    if let Some(method) = unit.getMethodByName(method_signature)? {
+                        for instruction in method.getDalvikInstructions()? {
+                            method_opcodes.insert(instruction.getOffset()?, instruction.getOpcode()?);
+                        }
+                    }
+                    opcodes.insert(method_signature.to_string(), method_opcodes);
+                }
+                opcodes[method_signature]
+                    .get(&offset)
+                    .map_or(XrefKind::Reference, |opcode| XrefKind::from_opcode(*opcode))
+            }
+            None => XrefKind::Reference,
+        };
+        xrefs.push(Xref {
+            method_signature: method_signature.to_string(),
+            offset,
+            kind,
+        });
+    }
+    Ok(xrefs)
+}
+
+/// The pool items the code of `method` references, in instruction order.
+pub fn references_from(method: &dyn IDexMethod) -> Result<'static, Vec<Reference>> {
+    let mut references = vec![];
+    for instruction in method.getDalvikInstructions()? {
+        let opcode = instruction.getOpcode()?;
+        let pool_type = match opcode.index_pool_type() {
+            Some(pool_type) => pool_type,
+            None => continue,
+        };
+        for operand in instruction.getOperands()? {
+            if let DalvikOperand::Index(index) = operand {
+                references.push(Reference {
+                    offset: instruction.getOffset()?,
+                    kind: XrefKind::from_opcode(opcode),
+                    pool_type,
+                    index: index as i32,
+                });
+                break;
+            }
+        }
+    }
+    Ok(references)
+}
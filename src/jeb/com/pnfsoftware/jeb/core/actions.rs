@@ -0,0 +1,132 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+package_name!("actions");
+
+use super::units::IInteractiveUnit;
+
+/// The actions of `com.pnfsoftware.jeb.core.actions.Actions` this crate binds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Actions {
+    Rename,
+    Comment,
+    MoveToPackage,
+}
+
+impl Actions {
+    /// The action id, read from the JEB constants.
+    pub fn id(&self) -> Result<'static, i32> {
+        let name = match self {
+            Actions::Rename => "RENAME",
+            Actions::Comment => "COMMENT",
+            Actions::MoveToPackage => "MOVE_TO_PACKAGE",
+        };
+        let env = get_vm!();
+        let id = env.get_static_field(Actions_, name, "I")?.i()?;
+        Ok(id)
+    }
+}
+
+pub trait IActionData<'a>: Instance {}
+
+jclass! {ActionContext, ActionContext_}
+jclass! {ActionRenameData, ActionRenameData_}
+jclass! {ActionCommentData, ActionCommentData_}
+jclass! {ActionMoveToPackageData, ActionMoveToPackageData_}
+#[derive(ClassFromStr)]
+struct Actions_;
+
+impl<'a> IActionData<'a> for ActionRenameData<'a> {}
+impl<'a> IActionData<'a> for ActionCommentData<'a> {}
+impl<'a> IActionData<'a> for ActionMoveToPackageData<'a> {}
+
+impl<'a> ActionContext<'a> {
+    pub fn new(
+        unit: &dyn IInteractiveUnit,
+        action: Actions,
+        item_id: i64,
+        address: Option<&str>,
+    ) -> Result<'a, ActionContext<'a>> {
+        let env = get_vm!();
+        let address = match address {
+            Some(address) => env.new_string(address)?.into(),
+            None => jni::objects::JObject::null(),
+        };
+        let args = [
+            unit.get_obj()?.into(),
+            action.id()?.into(),
+            item_id.into(),
+            address.into(),
+        ];
+        let obj = env.new_object(
+            ActionContext_,
+            normalize!("(Lcom.pnfsoftware.jeb.core.units.IInteractiveUnit;IJLjava.lang.String;)V"),
+            &args,
+        )?;
+        Ok(ActionContext(obj.into()))
+    }
+}
+
+impl<'a> ActionRenameData<'a> {
+    pub fn new() -> Result<'a, ActionRenameData<'a>> {
+        let env = get_vm!();
+        let obj = env.new_object(ActionRenameData_, "()V", &[])?;
+        Ok(ActionRenameData(obj.into()))
+    }
+    pub fn getCurrentName(&self) -> Result<String> {
+        call!([String]self, "getCurrentName", "()Ljava/lang/String;", &[])
+    }
+    pub fn getOriginalName(&self) -> Result<String> {
+        call!([String]self, "getOriginalName", "()Ljava/lang/String;", &[])
+    }
+    pub fn setNewName(&self, name: &str) -> Result<()> {
+        let env = get_vm!();
+        let args = [env.new_string(name)?.into()];
+        env.call_method(self.get_obj()?, "setNewName", "(Ljava/lang/String;)V", &args)?;
+        Ok(())
+    }
+}
+
+impl<'a> ActionCommentData<'a> {
+    pub fn new() -> Result<'a, ActionCommentData<'a>> {
+        let env = get_vm!();
+        let obj = env.new_object(ActionCommentData_, "()V", &[])?;
+        Ok(ActionCommentData(obj.into()))
+    }
+    /// The comment before the action, `None` if there is none.
+    pub fn getCurrentComment(&self) -> Result<Option<String>> {
+        let env = get_vm!();
+        let res = env
+            .call_method(self.get_obj()?, "getCurrentComment", "()Ljava/lang/String;", &[])?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        let comment: String = env.get_string(res.into())?.into();
+        Ok(Some(comment))
+    }
+    pub fn setNewComment(&self, comment: &str) -> Result<()> {
+        let env = get_vm!();
+        let args = [env.new_string(comment)?.into()];
+        env.call_method(self.get_obj()?, "setNewComment", "(Ljava/lang/String;)V", &args)?;
+        Ok(())
+    }
+}
+
+impl<'a> ActionMoveToPackageData<'a> {
+    pub fn new() -> Result<'a, ActionMoveToPackageData<'a>> {
+        let env = get_vm!();
+        let obj = env.new_object(ActionMoveToPackageData_, "()V", &[])?;
+        Ok(ActionMoveToPackageData(obj.into()))
+    }
+    /// `fqname` is the dotted name of the target package, e.g. `com.example.ui`.
+    pub fn setDstPackageFqname(&self, fqname: &str) -> Result<()> {
+        let env = get_vm!();
+        let args = [env.new_string(fqname)?.into()];
+        env.call_method(self.get_obj()?, "setDstPackageFqname", "(Ljava/lang/String;)V", &args)?;
+        Ok(())
+    }
+}
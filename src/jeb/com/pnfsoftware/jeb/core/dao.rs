@@ -37,6 +37,38 @@ pub mod impl_ {
                     vec![jstring!(base_dir)]
                 }
         }
+
+        /// Lists the keys of all projects saved in `base_dir`.
+        ///
+        /// Both this and `has_project` read the directory directly and rely on the layout of the
+        /// `JDB2Manager`, which saves the project with key `key` as `<base_dir>/<key>.jdb2`. A
+        /// project database with another layout is not seen by either function.
+        pub fn list_project_keys(base_dir: &str) -> Result<'a, Vec<String>> {
+            let mut keys = vec![];
+            for entry in std::fs::read_dir(base_dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "jdb2")
+                    && std::fs::metadata(&path)?.is_file()
+                {
+                    if let Some(key) = path.file_stem().and_then(|stem| stem.to_str()) {
+                        keys.push(key.to_string());
+                    }
+                }
+            }
+            keys.sort();
+            Ok(keys)
+        }
+
+        /// Returns true if a project with the given key was saved in `base_dir` as
+        /// `<base_dir>/<key>.jdb2`, see `list_project_keys`.
+        pub fn has_project(base_dir: &str, key: &str) -> Result<'a, bool> {
+            let path = std::path::Path::new(base_dir).join(format!("{}.jdb2", key));
+            match std::fs::metadata(path) {
+                Ok(metadata) => Ok(metadata.is_file()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+                Err(e) => Err(e.into()),
+            }
+        }
     }
 
     impl<'a> SimpleFSFileStore<'a> {
@@ -70,4 +102,30 @@ pub mod impl_ {
     impl<'a> IFileStore<'a> for SimpleFSFileStore<'a> {}
     impl<'a> IFileDatabase<'a> for JDB2Manager<'a> {}
     impl<'a> IDataProvider<'a> for DataProvider<'a> {}
-}
\ No newline at end of file
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn project_files() {
+            let dir = std::env::temp_dir().join(format!("rusty-jeb-dao-{}", std::process::id()));
+            std::fs::create_dir_all(dir.join("nested.jdb2")).unwrap();
+            std::fs::write(dir.join("beta.jdb2"), b"").unwrap();
+            std::fs::write(dir.join("alpha.jdb2"), b"").unwrap();
+            std::fs::write(dir.join("notes.txt"), b"").unwrap();
+            let base_dir = dir.to_str().unwrap();
+
+            let keys = JDB2Manager::list_project_keys(base_dir);
+            let found = JDB2Manager::has_project(base_dir, "alpha");
+            let missing = JDB2Manager::has_project(base_dir, "notes");
+            let directory = JDB2Manager::has_project(base_dir, "nested");
+            std::fs::remove_dir_all(&dir).unwrap();
+
+            assert_eq!(keys.unwrap(), vec!["alpha", "beta"]);
+            assert!(found.unwrap());
+            assert!(!missing.unwrap());
+            assert!(!directory.unwrap());
+        }
+    }
+}
@@ -6,7 +6,13 @@
 
 package_name!("input");
 
+pub trait IInput<'a>: Instance {
+    fn getCurrentSize(&self) -> Result<i64>;
+}
+
 jclass! {FileInput, FileInput_}
+jclass! {BytesInput, BytesInput_}
+jclass! {JebInput, JebInput_}
 
 impl<'a> FileInput<'a> {
     constructor! {
@@ -25,4 +31,35 @@ impl<'a> FileInput<'a> {
             jargs!{path}
         }
     }
-}
\ No newline at end of file
+}
+
+impl<'a> BytesInput<'a> {
+    pub fn from_bytes<'t>(data: &[u8]) -> Result<'t, BytesInput<'t>> {
+        let env = get_vm!();
+        let array = jni::objects::JObject::from(env.byte_array_from_slice(data)?);
+        let obj = env.new_object(BytesInput_, "([B)V", &[array.into()])?;
+        Ok(BytesInput(obj.into()))
+    }
+    /// Reads `reader` to the end and keeps the content in memory, nothing is written to disk.
+    pub fn from_reader<'t, R: std::io::Read>(mut reader: R) -> Result<'t, BytesInput<'t>> {
+        let mut data = vec![];
+        reader.read_to_end(&mut data)?;
+        BytesInput::from_bytes(&data)
+    }
+}
+
+impl<'a> IInput<'a> for FileInput<'a> {
+    fn getCurrentSize(&self) -> Result<i64> {
+        call!([i64]self, "getCurrentSize", "()J", &[])
+    }
+}
+impl<'a> IInput<'a> for BytesInput<'a> {
+    fn getCurrentSize(&self) -> Result<i64> {
+        call!([i64]self, "getCurrentSize", "()J", &[])
+    }
+}
+impl<'a> IInput<'a> for JebInput<'a> {
+    fn getCurrentSize(&self) -> Result<i64> {
+        call!([i64]self, "getCurrentSize", "()J", &[])
+    }
+}
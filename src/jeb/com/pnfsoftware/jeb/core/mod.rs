@@ -6,9 +6,11 @@
 
 package_name!("core");
 
+pub mod actions;
 pub mod dao;
 pub mod events;
 pub mod input;
+pub mod output;
 pub mod properties;
 pub mod units;
 pub mod util;
@@ -17,7 +19,10 @@ use crate::jeb::*;
 
 use self::units::code::debug::{DebuggerUnitIdentifier, IDebuggerUnitIdentifier};
 
-pub trait IArtifact<'a>: Instance {}
+pub trait IArtifact<'a>: Instance {
+    fn getName(&self) -> Result<String>;
+    fn getCreationTimestamp(&self) -> Result<i64>;
+}
 pub trait ICoreContext<'a>: Instance {
     fn createEnginesContext(
         &self,
@@ -27,8 +32,22 @@ pub trait ICoreContext<'a>: Instance {
     fn closeEnginesContext(&self, context: Option<&dyn IEnginesContext>) -> Result<()>;
 }
 pub trait IEnginesContext<'a>: Instance {
+    /// Loads the project with the given key. If the project database of the data provider holds
+    /// a saved project with that key, it is restored (see `IRuntimeProject::isReloaded`),
+    /// otherwise a new empty project is created.
     fn loadProject(&self, str: &str) -> Result<Box<dyn IRuntimeProject + '_>>;
     fn unloadProject(&self, key: &str) -> Result<bool>;
+    /// Saves the loaded project `key` to the project database. If no `persistence_key` is given,
+    /// the project key is used, so that the next `loadProject(key)` restores it.
+    fn saveProject(&self, key: &str, persistence_key: Option<&str>) -> Result<bool>;
+    fn getProjects(&self) -> Result<Vec<Box<dyn IRuntimeProject + '_>>>;
+    /// The loaded project `key`, `None` if no project with that key is loaded.
+    fn getProject(&self, key: &str) -> Result<Option<Box<dyn IRuntimeProject + '_>>>;
+    fn getProjectCount(&self) -> Result<i32>;
+    fn getPropertyManager(&self) -> Result<Box<dyn properties::IPropertyManager + '_>>;
+    fn getPropertyDefinitionManager(
+        &self,
+    ) -> Result<Box<dyn properties::IPropertyDefinitionManager + '_>>;
     fn getDebuggerUnitIdentifiers(&self) -> Result<Vec<Box<dyn IDebuggerUnitIdentifier + '_>>>;
     fn isIdentifierEnabled(&self, identifier: Option<&dyn IDebuggerUnitIdentifier>)
         -> Result<bool>;
@@ -41,13 +60,34 @@ pub trait IEnginesContext<'a>: Instance {
 
 pub trait ILiveArtifact<'a>: Instance {
     fn getUnits(&self) -> Result<Vec<Box<dyn units::IUnit + '_>>>;
+    fn getArtifact(&self) -> Result<Option<Box<dyn IArtifact + '_>>>;
+    /// The root unit of the artifact, `None` until the artifact has been processed.
+    fn getMainUnit(&self) -> Result<Option<Box<dyn units::IUnit + '_>>>;
+    fn getRuntimeProject(&self) -> Result<Box<dyn IRuntimeProject + '_>>;
 }
 pub trait IRuntimeProject<'a>: Instance {
     fn processArtifact(
         &self,
         artifact: Option<&dyn IArtifact>,
     ) -> Result<Box<dyn ILiveArtifact + '_>>;
+    /// Processes several artifacts, e.g. an app and its split APKs, into this project. The live
+    /// artifacts are returned in the order of `artifacts`.
+    fn processArtifacts(
+        &self,
+        artifacts: &[&dyn IArtifact],
+    ) -> Result<Vec<Box<dyn ILiveArtifact + '_>>>;
     fn getKey(&self) -> Result<String>;
+    fn getName(&self) -> Result<String>;
+    fn getLiveArtifacts(&self) -> Result<Vec<Box<dyn ILiveArtifact + '_>>>;
+    fn getEnginesContext(&self) -> Result<Box<dyn IEnginesContext + '_>>;
+    fn getPropertyManager(&self) -> Result<Box<dyn properties::IPropertyManager + '_>>;
+    fn isReloaded(&self) -> Result<bool>;
+    /// Finds the first unit of the project whose name is `name`, searching the whole unit tree.
+    fn findUnitByName(&self, name: &str) -> Result<Box<dyn units::IUnit + '_>>;
+    /// Finds a unit by its path in the unit tree, e.g. `test.apk/Bytecode`. The first segment is
+    /// the name of an artifact's main unit, each further segment the name of a child unit.
+    fn findUnitByPath(&self, path: &str) -> Result<Box<dyn units::IUnit + '_>>;
+    fn destroy(&self) -> Result<()>;
 }
 
 jclass! {RuntimeProjectUtil, RuntimeProjectUtil_}
@@ -120,7 +160,15 @@ impl<'a> Artifact<'a> {
         let obj = env.new_object("java/io/File", "(Ljava/lang/String;)V", &args)?;
 
         let file = file::File(obj.into());
-        let file_input = Some(input::FileInput::from_file(Some(file))?);
+        let file_input = input::FileInput::from_file(Some(file))?;
+        Artifact::from_input(file_name, Some(&file_input))
+    }
+
+    pub fn from_input<'t>(
+        name: &str,
+        input: Option<&dyn input::IInput>,
+    ) -> Result<'t, Box<dyn IArtifact<'t> + 't>> {
+        let env = get_vm!();
         let ctor_sig = format!(
             "(L{};L{};)V",
             "java/lang/String",
@@ -130,16 +178,38 @@ impl<'a> Artifact<'a> {
             )
         );
 
-        let mut args = jargs! {file_input};
+        let mut args = jargs! {input};
 
-        args.insert(0, jstring! {file_name});
+        args.insert(0, jstring! {name});
         let res = env.new_object(Artifact_, ctor_sig, &args)?;
 
         Ok(Box::new(Artifact(res.into())))
     }
+
+    /// Creates an artifact held in memory, e.g. an APK or dex blob received over the network.
+    pub fn from_bytes<'t>(name: &str, data: &[u8]) -> Result<'t, Box<dyn IArtifact<'t> + 't>> {
+        let bytes_input = input::BytesInput::from_bytes(data)?;
+        Artifact::from_input(name, Some(&bytes_input))
+    }
+
+    /// Creates an in-memory artifact from the content of `reader`.
+    pub fn from_reader<'t, R: std::io::Read>(
+        name: &str,
+        reader: R,
+    ) -> Result<'t, Box<dyn IArtifact<'t> + 't>> {
+        let bytes_input = input::BytesInput::from_reader(reader)?;
+        Artifact::from_input(name, Some(&bytes_input))
+    }
 }
 
-impl<'a> IArtifact<'a> for Artifact<'a> {}
+impl<'a> IArtifact<'a> for Artifact<'a> {
+    fn getName(&self) -> Result<String> {
+        call!([String]self, "getName", "()Ljava/lang/String;", &[])
+    }
+    fn getCreationTimestamp(&self) -> Result<i64> {
+        call!([i64]self, "getCreationTimestamp", "()J", &[])
+    }
+}
 
 impl<'a> JebCoreService<'a> {
     pub fn getInstance<'t>(license_key: &'t str) -> Result<Box<dyn ICoreContext + 't>> {
@@ -217,6 +287,30 @@ impl<'a> ILiveArtifact<'a> for JebLiveArtifact<'a> {
             vec![]
         }
     }
+    fn getArtifact(&self) -> Result<Option<Box<dyn IArtifact + '_>>> {
+        let env = get_vm!();
+        let res = env
+            .call_method(self.get_obj()?, "getArtifact", normalize!("()Lcom.pnfsoftware.jeb.core.IArtifact;"), &[])?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(Artifact(res.into()))))
+    }
+    fn getMainUnit(&self) -> Result<Option<Box<dyn units::IUnit + '_>>> {
+        let env = get_vm!();
+        let res = env
+            .call_method(self.get_obj()?, "getMainUnit", normalize!("()Lcom.pnfsoftware.jeb.core.units.IUnit;"), &[])?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(units::JebUnit(res.into()))))
+    }
+    fn getRuntimeProject(&self) -> Result<Box<dyn IRuntimeProject + '_>> {
+        let res = call!(self, "getRuntimeProject", "()Lcom.pnfsoftware.jeb.core.IRuntimeProject;", &[])?;
+        box_ok!(JebRuntimeProject(res))
+    }
 }
 
 impl<'a> IRuntimeProject<'a> for JebRuntimeProject<'a> {
@@ -237,6 +331,16 @@ impl<'a> IRuntimeProject<'a> for JebRuntimeProject<'a> {
         )?;
         Ok(Box::new(JebLiveArtifact(res)))
     }
+    fn processArtifacts(
+        &self,
+        artifacts: &[&dyn IArtifact],
+    ) -> Result<Vec<Box<dyn ILiveArtifact + '_>>> {
+        let mut live_artifacts = vec![];
+        for artifact in artifacts {
+            live_artifacts.push(self.processArtifact(Some(*artifact))?);
+        }
+        Ok(live_artifacts)
+    }
     fn getKey(&self) -> Result<String> {
         let env = get_vm!();
         let res = call!(self, "getKey", "()Ljava/lang/String;", &[])?;
@@ -244,6 +348,65 @@ impl<'a> IRuntimeProject<'a> for JebRuntimeProject<'a> {
         let the_string = env.get_string(obj.into())?;
         Ok(the_string.into())
     }
+    fn getName(&self) -> Result<String> {
+        call!([String]self, "getName", "()Ljava/lang/String;", &[])
+    }
+    jcall! {
+        Vec<ILiveArtifact>
+        [JebLiveArtifact]
+        fn getLiveArtifacts()
+    }
+    fn getEnginesContext(&self) -> Result<Box<dyn IEnginesContext + '_>> {
+        let res = call!(self, "getEnginesContext", "()Lcom.pnfsoftware.jeb.core.IEnginesContext;", &[])?;
+        box_ok!(JebEnginesContext(res))
+    }
+    fn getPropertyManager(&self) -> Result<Box<dyn properties::IPropertyManager + '_>> {
+        let res = call!(self, "getPropertyManager", "()Lcom.pnfsoftware.jeb.core.properties.IPropertyManager;", &[])?;
+        Ok(Box::new(properties::PropertyManager(res)))
+    }
+    fn isReloaded(&self) -> Result<bool> {
+        call!([Bool]self, "isReloaded", "()Z", &[])
+    }
+    fn findUnitByName(&self, name: &str) -> Result<Box<dyn units::IUnit + '_>> {
+        for unit in RuntimeProjectUtil::getAllUnits(Some(self))? {
+            if unit.getName()? == name {
+                return Ok(unit);
+            }
+        }
+        Err(format!("no unit named {}", name).into())
+    }
+    fn findUnitByPath(&self, path: &str) -> Result<Box<dyn units::IUnit + '_>> {
+        let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+        let root = segments.next().ok_or("unit path is empty")?;
+        let mut current = None;
+        for live_artifact in self.getLiveArtifacts()? {
+            let main_unit = match live_artifact.getMainUnit()? {
+                Some(main_unit) => main_unit,
+                None => continue,
+            };
+            if main_unit.getName()? == root {
+                current = Some(main_unit.get_obj()?.into_inner());
+                break;
+            }
+        }
+        let mut current = current.ok_or_else(|| format!("no artifact with main unit {}", root))?;
+        for segment in segments {
+            let unit = units::JebUnit(jni::objects::JObject::from(current).into());
+            let mut next = None;
+            for child in units::IUnit::getChildren(&unit)? {
+                if child.getName()? == segment {
+                    next = Some(child.get_obj()?.into_inner());
+                    break;
+                }
+            }
+            current = next.ok_or_else(|| format!("no unit at {}", path))?;
+        }
+        Ok(Box::new(units::JebUnit(jni::objects::JObject::from(current).into())))
+    }
+    fn destroy(&self) -> Result<()> {
+        call!(self, "destroy", "()V", &[])?;
+        Ok(())
+    }
 }
 
 impl<'a> IEnginesContext<'a> for JebEnginesContext<'a> {
@@ -264,6 +427,45 @@ impl<'a> IEnginesContext<'a> for JebEnginesContext<'a> {
         let res = call! {self, "unloadProject", "(Ljava/lang/String;)Z", &args}?;
         Ok(res.z()?)
     }
+    fn saveProject(&self, key: &str, persistence_key: Option<&str>) -> Result<bool> {
+        let persistence_key = persistence_key.unwrap_or(key);
+        let args = vec![
+            jstring!(key),
+            jstring!(persistence_key),
+            jni::objects::JObject::null().into(),
+            jni::objects::JObject::null().into(),
+        ];
+        call!([Bool]self, "saveProject", normalize!("(Ljava.lang.String;Ljava.lang.String;Ljava.util.Map;Lcom.pnfsoftware.jeb.util.base.IProgressCallback;)Z"), &args)
+    }
+    jcall! {
+        Vec<IRuntimeProject>
+        [JebRuntimeProject]
+        fn getProjects()
+    }
+    fn getProject(&self, key: &str) -> Result<Option<Box<dyn IRuntimeProject + '_>>> {
+        let env = get_vm!();
+        let args = [jstring!(key)];
+        let res = env
+            .call_method(self.get_obj()?, "getProject", normalize!("(Ljava/lang/String;)Lcom.pnfsoftware.jeb.core.IRuntimeProject;"), &args)?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(JebRuntimeProject(res.into()))))
+    }
+    fn getProjectCount(&self) -> Result<i32> {
+        call!([i32]self, "getProjectCount", "()I", &[])
+    }
+    fn getPropertyManager(&self) -> Result<Box<dyn properties::IPropertyManager + '_>> {
+        let res = call!(self, "getPropertyManager", "()Lcom.pnfsoftware.jeb.core.properties.IPropertyManager;", &[])?;
+        Ok(Box::new(properties::PropertyManager(res)))
+    }
+    fn getPropertyDefinitionManager(
+        &self,
+    ) -> Result<Box<dyn properties::IPropertyDefinitionManager + '_>> {
+        let res = call!(self, "getPropertyDefinitionManager", "()Lcom.pnfsoftware.jeb.core.properties.IPropertyDefinitionManager;", &[])?;
+        Ok(Box::new(properties::PropertyDefinitionManager(res)))
+    }
 
     jcall! {
         Vec<IDebuggerUnitIdentifier>
@@ -0,0 +1,432 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+package_name!("output");
+
+pub trait IUnitFormatter<'a>: Instance {
+    fn getPresentations(&self) -> Result<Vec<Box<dyn IUnitDocumentPresentation + '_>>>;
+    fn getPresentation(&self, index: i32) -> Result<Box<dyn IUnitDocumentPresentation + '_>>;
+}
+pub trait IUnitDocumentPresentation<'a>: Instance {
+    fn getId(&self) -> Result<i64>;
+    fn getLabel(&self) -> Result<String>;
+    fn isDefaultRepresentation(&self) -> Result<bool>;
+    fn getDocument(&self) -> Result<Box<dyn IGenericDocument + '_>>;
+}
+pub trait IGenericDocument<'a>: Instance {
+    fn dispose(&self) -> Result<()>;
+}
+
+jclass! {JebUnitFormatter, JebUnitFormatter_}
+jclass! {JebUnitDocumentPresentation, JebUnitDocumentPresentation_}
+jclass! {JebGenericDocument, JebGenericDocument_}
+
+impl<'a> IUnitFormatter<'a> for JebUnitFormatter<'a> {
+    jcall! {
+        Vec<IUnitDocumentPresentation>
+        [JebUnitDocumentPresentation]
+        fn getPresentations()
+    }
+    fn getPresentation(&self, index: i32) -> Result<Box<dyn IUnitDocumentPresentation + '_>> {
+        let res = call!(self, "getPresentation", "(I)Lcom.pnfsoftware.jeb.core.output.IUnitDocumentPresentation;", &[index.into()])?;
+        box_ok!(JebUnitDocumentPresentation(res))
+    }
+}
+
+impl<'a> IUnitDocumentPresentation<'a> for JebUnitDocumentPresentation<'a> {
+    fn getId(&self) -> Result<i64> {
+        call!([i64]self, "getId", "()J", &[])
+    }
+    fn getLabel(&self) -> Result<String> {
+        call!([String]self, "getLabel", "()Ljava/lang/String;", &[])
+    }
+    fn isDefaultRepresentation(&self) -> Result<bool> {
+        call!([Bool]self, "isDefaultRepresentation", "()Z", &[])
+    }
+    fn getDocument(&self) -> Result<Box<dyn IGenericDocument + '_>> {
+        let res = call!(self, "getDocument", "()Lcom.pnfsoftware.jeb.core.output.IGenericDocument;", &[])?;
+        box_ok!(JebGenericDocument(res))
+    }
+}
+
+impl<'a> IGenericDocument<'a> for JebGenericDocument<'a> {
+    fn dispose(&self) -> Result<()> {
+        call!(self, "dispose", "()V", &[])?;
+        Ok(())
+    }
+}
+
+fn is_instance_of(value: &dyn IGenericDocument, class_name: &str) -> Result<'static, bool> {
+    let env = get_vm!();
+    let class = env.find_class(normalize!(class_name))?;
+    Ok(env.is_instance_of(value.get_obj()?, class)?)
+}
+
+pub mod text {
+    package_name!("text");
+    use super::{is_instance_of, IGenericDocument};
+
+    pub trait ITextDocument<'a>: Instance {
+        fn getAnchorCount(&self) -> Result<i64>;
+        fn getFirstAnchor(&self) -> Result<i64>;
+        fn getInitialAnchor(&self) -> Result<i64>;
+        /// Returns the lines around `anchor_id`, this is how long documents are read page by page.
+        fn getDocumentPart(
+            &self,
+            anchor_id: i64,
+            lines_after: i32,
+            lines_before: i32,
+        ) -> Result<Box<dyn ITextDocumentPart + '_>>;
+        /// `None` if the address is not in the document.
+        fn addressToCoordinates(&self, address: &str) -> Result<Option<Box<dyn ICoordinates + '_>>>;
+        fn coordinatesToAddress(&self, coordinates: Option<&dyn ICoordinates>) -> Result<String>;
+        /// Returns the whole document as text, lines are separated by `\n`.
+        fn getText(&self) -> Result<String>;
+    }
+    pub trait ITextDocumentPart<'a>: Instance {
+        fn getLines(&self) -> Result<Vec<Box<dyn ILine + '_>>>;
+        fn getAnchors(&self) -> Result<Vec<Box<dyn IAnchor + '_>>>;
+    }
+    pub trait ILine<'a>: Instance {
+        fn getText(&self) -> Result<String>;
+    }
+    pub trait IAnchor<'a>: Instance {
+        fn getIdentifier(&self) -> Result<i64>;
+        fn getLineIndex(&self) -> Result<i32>;
+    }
+    pub trait ICoordinates<'a>: Instance {
+        fn getAnchorId(&self) -> Result<i64>;
+        fn getLineDelta(&self) -> Result<i32>;
+        fn getColumnOffset(&self) -> Result<i32>;
+    }
+
+    /// The number of lines `ITextDocument::getText` requests per document part.
+    const TEXT_PAGE_LINES: i32 = 1000;
+
+    jclass! {JebTextDocument, JebTextDocument_}
+    jclass! {JebTextDocumentPart, JebTextDocumentPart_}
+    jclass! {JebLine, JebLine_}
+    jclass! {JebAnchor, JebAnchor_}
+    jclass! {JebCoordinates, JebCoordinates_}
+
+    impl<'a> ITextDocument<'a> for JebTextDocument<'a> {
+        fn getAnchorCount(&self) -> Result<i64> {
+            call!([i64]self, "getAnchorCount", "()J", &[])
+        }
+        fn getFirstAnchor(&self) -> Result<i64> {
+            call!([i64]self, "getFirstAnchor", "()J", &[])
+        }
+        fn getInitialAnchor(&self) -> Result<i64> {
+            call!([i64]self, "getInitialAnchor", "()J", &[])
+        }
+        fn getDocumentPart(
+            &self,
+            anchor_id: i64,
+            lines_after: i32,
+            lines_before: i32,
+        ) -> Result<Box<dyn ITextDocumentPart + '_>> {
+            let args = [anchor_id.into(), lines_after.into(), lines_before.into()];
+            let res = call!(self, "getDocumentPart", "(JII)Lcom.pnfsoftware.jeb.core.output.text.ITextDocumentPart;", &args)?;
+            box_ok!(JebTextDocumentPart(res))
+        }
+        fn addressToCoordinates(&self, address: &str) -> Result<Option<Box<dyn ICoordinates + '_>>> {
+            let env = get_vm!();
+            let args = [jstring!(address)];
+            let res = env
+                .call_method(self.get_obj()?, "addressToCoordinates", normalize!("(Ljava.lang.String;)Lcom.pnfsoftware.jeb.core.output.text.ICoordinates;"), &args)?
+                .l()?;
+            if res.is_null() {
+                return Ok(None);
+            }
+            Ok(Some(Box::new(JebCoordinates(res.into()))))
+        }
+        fn coordinatesToAddress(&self, coordinates: Option<&dyn ICoordinates>) -> Result<String> {
+            let args = jargs!(coordinates);
+            call!([String]self, "coordinatesToAddress", "(Lcom.pnfsoftware.jeb.core.output.text.ICoordinates;)Ljava.lang.String;", &args)
+        }
+        fn getText(&self) -> Result<String> {
+            let end = self.getFirstAnchor()? + self.getAnchorCount()?;
+            let mut anchor = self.getFirstAnchor()?;
+            let mut page_lines = TEXT_PAGE_LINES;
+            let mut lines = vec![];
+            while anchor < end {
+                let part = self.getDocumentPart(anchor, page_lines, 0)?;
+                let part_lines = part.getLines()?;
+                let mut anchors = vec![];
+                for part_anchor in part.getAnchors()? {
+                    anchors.push((part_anchor.getIdentifier()?, part_anchor.getLineIndex()?));
+                }
+                let last_anchor = anchors.iter().map(|(id, _)| *id).max().unwrap_or(anchor);
+                if part_lines.len() < page_lines as usize {
+                    // the part holds everything up to the end of the document
+                    for line in part_lines {
+                        lines.push(line.getText()?);
+                    }
+                    if last_anchor < anchor {
+                        break;
+                    }
+                    anchor = last_anchor + 1;
+                    continue;
+                }
+                // a full page may end in the middle of its last anchor, which is read again on the
+                // next page
+                match anchors.iter().filter(|(id, _)| *id > anchor).max() {
+                    Some(&(next_anchor, line_index)) => {
+                        for line in part_lines.iter().take(line_index as usize) {
+                            lines.push(line.getText()?);
+                        }
+                        anchor = next_anchor;
+                        page_lines = TEXT_PAGE_LINES;
+                    }
+                    // a single anchor longer than the page
+                    None => page_lines = page_lines.saturating_mul(2),
+                }
+            }
+            Ok(lines.join("\n"))
+        }
+    }
+
+    impl<'a> ITextDocumentPart<'a> for JebTextDocumentPart<'a> {
+        jcall! {
+            Vec<ILine>
+            [JebLine]
+            fn getLines()
+        }
+        jcall! {
+            Vec<IAnchor>
+            [JebAnchor]
+            fn getAnchors()
+        }
+    }
+
+    impl<'a> ILine<'a> for JebLine<'a> {
+        fn getText(&self) -> Result<String> {
+            let res = call!(self, "getText", "()Ljava.lang.CharSequence;", &[])?;
+            call_object!([String]res.l()?, "toString", "()Ljava.lang.String;", &[])
+        }
+    }
+
+    impl<'a> IAnchor<'a> for JebAnchor<'a> {
+        fn getIdentifier(&self) -> Result<i64> {
+            call!([i64]self, "getIdentifier", "()J", &[])
+        }
+        fn getLineIndex(&self) -> Result<i32> {
+            call!([i32]self, "getLineIndex", "()I", &[])
+        }
+    }
+
+    impl<'a> ICoordinates<'a> for JebCoordinates<'a> {
+        fn getAnchorId(&self) -> Result<i64> {
+            call!([i64]self, "getAnchorId", "()J", &[])
+        }
+        fn getLineDelta(&self) -> Result<i32> {
+            call!([i32]self, "getLineDelta", "()I", &[])
+        }
+        fn getColumnOffset(&self) -> Result<i32> {
+            call!([i32]self, "getColumnOffset", "()I", &[])
+        }
+    }
+
+    impl<'a, 'b> TryFrom<&'b dyn IGenericDocument<'a>> for JebTextDocument<'b> {
+        type Error = Box<dyn std::error::Error>;
+
+        fn try_from(value: &'b dyn IGenericDocument<'a>) -> core::result::Result<Self, Self::Error> {
+            if is_instance_of(value, "com.pnfsoftware.jeb.core.output.text.ITextDocument")? {
+                Ok(JebTextDocument(value.get_obj()?.into()))
+            } else {
+                Err("Not a text document".into())
+            }
+        }
+    }
+}
+
+pub mod table {
+    package_name!("table");
+    use super::{is_instance_of, IGenericDocument};
+
+    pub trait ITableDocument<'a>: Instance {
+        fn getColumnLabels(&self) -> Result<Vec<String>>;
+        fn getRowCount(&self) -> Result<i32>;
+        fn getTable(&self) -> Result<Box<dyn ITableDocumentPart + '_>>;
+        fn getTablePart(&self, start: i32, count: i32) -> Result<Box<dyn ITableDocumentPart + '_>>;
+        /// Reads `count` rows starting at row `start` as the labels of their cells.
+        fn getCellLabels(&self, start: i32, count: i32) -> Result<Vec<Vec<String>>>;
+    }
+    pub trait ITableDocumentPart<'a>: Instance {
+        fn getFirstRowIndex(&self) -> Result<i32>;
+        fn getRows(&self) -> Result<Vec<Box<dyn ITableRow + '_>>>;
+    }
+    pub trait ITableRow<'a>: Instance {
+        fn getCells(&self) -> Result<Vec<Box<dyn ICell + '_>>>;
+    }
+    pub trait ICell<'a>: Instance {
+        fn getLabel(&self) -> Result<String>;
+    }
+
+    jclass! {JebTableDocument, JebTableDocument_}
+    jclass! {JebTableDocumentPart, JebTableDocumentPart_}
+    jclass! {JebTableRow, JebTableRow_}
+    jclass! {JebCell, JebCell_}
+
+    impl<'a> ITableDocument<'a> for JebTableDocument<'a> {
+        fn getColumnLabels(&self) -> Result<Vec<String>> {
+            let env = get_vm!();
+            let res = call!(self, "getColumnLabels", "()Ljava.util.List;", &[])?;
+            let list = jni::objects::JList::from_env(&env, res.l()?)?;
+            let mut labels: Vec<String> = vec![];
+            for label in list.iter()? {
+                labels.push(env.get_string(label.into())?.into());
+            }
+            Ok(labels)
+        }
+        fn getRowCount(&self) -> Result<i32> {
+            call!([i32]self, "getRowCount", "()I", &[])
+        }
+        fn getTable(&self) -> Result<Box<dyn ITableDocumentPart + '_>> {
+            let res = call!(self, "getTable", "()Lcom.pnfsoftware.jeb.core.output.table.ITableDocumentPart;", &[])?;
+            box_ok!(JebTableDocumentPart(res))
+        }
+        fn getTablePart(&self, start: i32, count: i32) -> Result<Box<dyn ITableDocumentPart + '_>> {
+            let res = call!(self, "getTablePart", "(II)Lcom.pnfsoftware.jeb.core.output.table.ITableDocumentPart;", &[start.into(), count.into()])?;
+            box_ok!(JebTableDocumentPart(res))
+        }
+        fn getCellLabels(&self, start: i32, count: i32) -> Result<Vec<Vec<String>>> {
+            let mut rows = vec![];
+            for row in self.getTablePart(start, count)?.getRows()? {
+                let mut cells = vec![];
+                for cell in row.getCells()? {
+                    cells.push(cell.getLabel()?);
+                }
+                rows.push(cells);
+            }
+            Ok(rows)
+        }
+    }
+
+    impl<'a> ITableDocumentPart<'a> for JebTableDocumentPart<'a> {
+        fn getFirstRowIndex(&self) -> Result<i32> {
+            call!([i32]self, "getFirstRowIndex", "()I", &[])
+        }
+        jcall! {
+            Vec<ITableRow>
+            [JebTableRow]
+            fn getRows()
+        }
+    }
+
+    impl<'a> ITableRow<'a> for JebTableRow<'a> {
+        jcall! {
+            Vec<ICell>
+            [JebCell]
+            fn getCells()
+        }
+    }
+
+    impl<'a> ICell<'a> for JebCell<'a> {
+        fn getLabel(&self) -> Result<String> {
+            call!([String]self, "getLabel", "()Ljava/lang/String;", &[])
+        }
+    }
+
+    impl<'a, 'b> TryFrom<&'b dyn IGenericDocument<'a>> for JebTableDocument<'b> {
+        type Error = Box<dyn std::error::Error>;
+
+        fn try_from(value: &'b dyn IGenericDocument<'a>) -> core::result::Result<Self, Self::Error> {
+            if is_instance_of(value, "com.pnfsoftware.jeb.core.output.table.ITableDocument")? {
+                Ok(JebTableDocument(value.get_obj()?.into()))
+            } else {
+                Err("Not a table document".into())
+            }
+        }
+    }
+}
+
+pub mod tree {
+    package_name!("tree");
+    use super::{is_instance_of, IGenericDocument};
+
+    pub trait ITreeDocument<'a>: Instance {
+        fn getColumnLabels(&self) -> Result<Vec<String>>;
+        fn getRoots(&self) -> Result<Vec<Box<dyn INode + '_>>>;
+        /// Reads the whole tree into plain `TreeNode`s.
+        fn getNodeTree(&self) -> Result<Vec<TreeNode>>;
+    }
+    pub trait INode<'a>: Instance {
+        fn getLabel(&self) -> Result<String>;
+        fn getChildren(&self) -> Result<Vec<Box<dyn INode + '_>>>;
+    }
+
+    /// A node of a tree document, detached from the JVM.
+    #[derive(Debug, Clone)]
+    pub struct TreeNode {
+        pub label: String,
+        pub children: Vec<TreeNode>,
+    }
+
+    impl TreeNode {
+        fn from_node(node: &dyn INode) -> Result<'static, TreeNode> {
+            let mut children = vec![];
+            for child in node.getChildren()? {
+                children.push(TreeNode::from_node(child.as_ref())?);
+            }
+            Ok(TreeNode {
+                label: node.getLabel()?,
+                children,
+            })
+        }
+    }
+
+    jclass! {JebTreeDocument, JebTreeDocument_}
+    jclass! {JebNode, JebNode_}
+
+    impl<'a> ITreeDocument<'a> for JebTreeDocument<'a> {
+        fn getColumnLabels(&self) -> Result<Vec<String>> {
+            let env = get_vm!();
+            let res = call!(self, "getColumnLabels", "()Ljava.util.List;", &[])?;
+            let list = jni::objects::JList::from_env(&env, res.l()?)?;
+            let mut labels: Vec<String> = vec![];
+            for label in list.iter()? {
+                labels.push(env.get_string(label.into())?.into());
+            }
+            Ok(labels)
+        }
+        jcall! {
+            Vec<INode>
+            [JebNode]
+            fn getRoots()
+        }
+        fn getNodeTree(&self) -> Result<Vec<TreeNode>> {
+            let mut nodes = vec![];
+            for root in self.getRoots()? {
+                nodes.push(TreeNode::from_node(root.as_ref())?);
+            }
+            Ok(nodes)
+        }
+    }
+
+    impl<'a> INode<'a> for JebNode<'a> {
+        fn getLabel(&self) -> Result<String> {
+            call!([String]self, "getLabel", "()Ljava/lang/String;", &[])
+        }
+        jcall! {
+            Vec<INode>
+            [JebNode]
+            fn getChildren()
+        }
+    }
+
+    impl<'a, 'b> TryFrom<&'b dyn IGenericDocument<'a>> for JebTreeDocument<'b> {
+        type Error = Box<dyn std::error::Error>;
+
+        fn try_from(value: &'b dyn IGenericDocument<'a>) -> core::result::Result<Self, Self::Error> {
+            if is_instance_of(value, "com.pnfsoftware.jeb.core.output.tree.ITreeDocument")? {
+                Ok(JebTreeDocument(value.get_obj()?.into()))
+            } else {
+                Err("Not a tree document".into())
+            }
+        }
+    }
+}
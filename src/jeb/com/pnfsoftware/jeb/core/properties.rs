@@ -7,6 +7,183 @@
 package_name!("properties");
 
 pub trait IConfiguration<'a>: crate::jeb::Instance {}
+pub trait IPropertyManager<'a>: crate::jeb::Instance {
+    fn getBoolean(&self, name: &str) -> Result<bool>;
+    fn getInteger(&self, name: &str) -> Result<i32>;
+    fn getString(&self, name: &str) -> Result<String>;
+    fn getValue(&self, name: &str) -> Result<PropertyValue>;
+    fn setBoolean(&self, name: &str, value: bool) -> Result<bool>;
+    fn setInteger(&self, name: &str, value: i32) -> Result<bool>;
+    fn setString(&self, name: &str, value: &str) -> Result<bool>;
+    fn setValue(&self, name: &str, value: &PropertyValue) -> Result<bool>;
+    fn getPropertyDefinitionManager(&self) -> Result<Box<dyn IPropertyDefinitionManager + '_>>;
+}
+pub trait IPropertyDefinitionManager<'a>: crate::jeb::Instance {
+    fn getNamespace(&self) -> Result<String>;
+    fn getRegion(&self) -> Result<String>;
+    fn getDefinitions(&self) -> Result<Vec<Box<dyn IPropertyDefinition + '_>>>;
+    /// The definition of the property `name`, `None` if the manager defines no such property.
+    fn getDefinition(&self, name: &str) -> Result<Option<Box<dyn IPropertyDefinition + '_>>>;
+    fn getChildren(&self) -> Result<Vec<Box<dyn IPropertyDefinitionManager + '_>>>;
+}
+pub trait IPropertyDefinition<'a>: crate::jeb::Instance {
+    fn getName(&self) -> Result<String>;
+    fn getDescription(&self) -> Result<String>;
+    fn getFlags(&self) -> Result<i32>;
+    fn getType(&self) -> Result<Box<dyn IPropertyType + '_>>;
+}
+pub trait IPropertyType<'a>: crate::jeb::Instance {
+    fn getName(&self) -> Result<String>;
+    fn getDefault(&self) -> Result<PropertyValue>;
+}
+
+/// A property value converted from the boxed Java value JEB stores.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Boolean(bool),
+    Integer(i32),
+    String(String),
+    /// Any other value, as given by its `toString`.
+    Other(String),
+    Null,
+}
+
+impl PropertyValue {
+    fn from_object(value: jni::objects::JObject) -> Result<'static, PropertyValue> {
+        let env = get_vm!();
+        if env.is_same_object(value, jni::objects::JObject::null())? {
+            Ok(PropertyValue::Null)
+        } else if env.is_instance_of(value, "java/lang/Boolean")? {
+            let res = env.call_method(value, "booleanValue", "()Z", &[])?;
+            Ok(PropertyValue::Boolean(res.z()?))
+        } else if env.is_instance_of(value, "java/lang/Integer")? {
+            let res = env.call_method(value, "intValue", "()I", &[])?;
+            Ok(PropertyValue::Integer(res.i()?))
+        } else if env.is_instance_of(value, "java/lang/String")? {
+            Ok(PropertyValue::String(env.get_string(value.into())?.into()))
+        } else {
+            let res = env.call_method(value, "toString", "()Ljava/lang/String;", &[])?;
+            Ok(PropertyValue::Other(env.get_string(res.l()?.into())?.into()))
+        }
+    }
+
+    fn to_object<'t>(&self) -> Result<'t, jni::objects::JObject<'t>> {
+        let env = get_vm!();
+        match self {
+            PropertyValue::Boolean(value) => Ok(env
+                .call_static_method("java/lang/Boolean", "valueOf", "(Z)Ljava/lang/Boolean;", &[(*value).into()])?
+                .l()?),
+            PropertyValue::Integer(value) => Ok(env
+                .call_static_method("java/lang/Integer", "valueOf", "(I)Ljava/lang/Integer;", &[(*value).into()])?
+                .l()?),
+            PropertyValue::String(value) | PropertyValue::Other(value) => {
+                Ok(env.new_string(value)?.into())
+            }
+            PropertyValue::Null => Ok(jni::objects::JObject::null()),
+        }
+    }
+}
+
+jclass! {PropertyManager, PropertyManager_}
+jclass! {PropertyDefinitionManager, PropertyDefinitionManager_}
+jclass! {PropertyDefinition, PropertyDefinition_}
+jclass! {PropertyType, PropertyType_}
+
+impl<'a> IPropertyManager<'a> for PropertyManager<'a> {
+    fn getBoolean(&self, name: &str) -> Result<bool> {
+        let args = vec![jstring!(name)];
+        call!([Bool]self, "getBoolean", "(Ljava/lang/String;)Z", &args)
+    }
+    fn getInteger(&self, name: &str) -> Result<i32> {
+        let args = vec![jstring!(name)];
+        call!([i32]self, "getInteger", "(Ljava/lang/String;)I", &args)
+    }
+    fn getString(&self, name: &str) -> Result<String> {
+        let args = vec![jstring!(name)];
+        call!([String]self, "getString", "(Ljava/lang/String;)Ljava/lang/String;", &args)
+    }
+    fn getValue(&self, name: &str) -> Result<PropertyValue> {
+        let args = vec![jstring!(name)];
+        let env = get_vm!();
+        let res = env.call_method(self.get_obj()?, "getValue", "(Ljava/lang/String;)Ljava/lang/Object;", &args)?;
+        PropertyValue::from_object(res.l()?)
+    }
+    fn setBoolean(&self, name: &str, value: bool) -> Result<bool> {
+        self.setValue(name, &PropertyValue::Boolean(value))
+    }
+    fn setInteger(&self, name: &str, value: i32) -> Result<bool> {
+        self.setValue(name, &PropertyValue::Integer(value))
+    }
+    fn setString(&self, name: &str, value: &str) -> Result<bool> {
+        self.setValue(name, &PropertyValue::String(value.to_string()))
+    }
+    fn setValue(&self, name: &str, value: &PropertyValue) -> Result<bool> {
+        let args = vec![jstring!(name), value.to_object()?.into()];
+        call!([Bool]self, "setValue", "(Ljava/lang/String;Ljava/lang/Object;)Z", &args)
+    }
+    fn getPropertyDefinitionManager(&self) -> Result<Box<dyn IPropertyDefinitionManager + '_>> {
+        let res = call!(self, "getPropertyDefinitionManager", "()Lcom.pnfsoftware.jeb.core.properties.IPropertyDefinitionManager;", &[])?;
+        box_ok!(PropertyDefinitionManager(res))
+    }
+}
+
+impl<'a> IPropertyDefinitionManager<'a> for PropertyDefinitionManager<'a> {
+    fn getNamespace(&self) -> Result<String> {
+        call!([String]self, "getNamespace", "()Ljava/lang/String;", &[])
+    }
+    fn getRegion(&self) -> Result<String> {
+        call!([String]self, "getRegion", "()Ljava/lang/String;", &[])
+    }
+    jcall! {
+        Vec<IPropertyDefinition>
+        [PropertyDefinition]
+        fn getDefinitions()
+    }
+    fn getDefinition(&self, name: &str) -> Result<Option<Box<dyn IPropertyDefinition + '_>>> {
+        let env = get_vm!();
+        let args = [jstring!(name)];
+        let res = env
+            .call_method(self.get_obj()?, "getDefinition", normalize!("(Ljava/lang/String;)Lcom.pnfsoftware.jeb.core.properties.IPropertyDefinition;"), &args)?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(PropertyDefinition(res.into()))))
+    }
+    jcall! {
+        Vec<IPropertyDefinitionManager>
+        [PropertyDefinitionManager]
+        fn getChildren()
+    }
+}
+
+impl<'a> IPropertyDefinition<'a> for PropertyDefinition<'a> {
+    fn getName(&self) -> Result<String> {
+        call!([String]self, "getName", "()Ljava/lang/String;", &[])
+    }
+    fn getDescription(&self) -> Result<String> {
+        call!([String]self, "getDescription", "()Ljava/lang/String;", &[])
+    }
+    fn getFlags(&self) -> Result<i32> {
+        call!([i32]self, "getFlags", "()I", &[])
+    }
+    fn getType(&self) -> Result<Box<dyn IPropertyType + '_>> {
+        let res = call!(self, "getType", "()Lcom.pnfsoftware.jeb.core.properties.IPropertyType;", &[])?;
+        box_ok!(PropertyType(res))
+    }
+}
+
+impl<'a> IPropertyType<'a> for PropertyType<'a> {
+    fn getName(&self) -> Result<String> {
+        call!([String]self, "getName", "()Ljava/lang/String;", &[])
+    }
+    fn getDefault(&self) -> Result<PropertyValue> {
+        let env = get_vm!();
+        let res = env.call_method(self.get_obj()?, "getDefault", "()Ljava/lang/Object;", &[])?;
+        PropertyValue::from_object(res.l()?)
+    }
+}
+
 pub mod impl_ {
    package_name!("impl");
 
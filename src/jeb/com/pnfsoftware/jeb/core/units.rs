@@ -6,14 +6,33 @@
 
 package_name!("units");
 
-use self::code::android::dex::DexPoolType;
+use self::code::asm::cfg::CFG;
+use self::code::android::dex::{
+    AccessFlags, DalvikArrayData, DalvikOpcode, DalvikOperand, DalvikSwitchData, DexAnnotation,
+    DexAddress, DexAnnotationElement, DexCatchHandler, DexLocalVariable, DexPoolType, DexTryBlock,
+    DexValue,
+};
 
+use super::actions::{
+    ActionCommentData, ActionContext, ActionMoveToPackageData, ActionRenameData, Actions,
+    IActionData,
+};
+use super::input::{IInput, JebInput};
+use super::output::{IUnitFormatter, JebUnitFormatter};
+use super::properties::{
+    IPropertyDefinitionManager, IPropertyManager, PropertyDefinitionManager, PropertyManager,
+};
 use super::util::{IDebuggerUnit, JebDebuggerUnit};
 
+use crate::jeb::org::w3c::dom::Document;
+
+use std::collections::{HashMap, HashSet};
+
 pub mod code {
     package_name!("code");
     pub mod debug {
         package_name!("debug");
+        use super::android::dex::DexAddress;
         use crate::jeb::com::pnfsoftware::jeb::core::util::{ITypedValue, TypedVariable};
 
         pub mod impl_ {
@@ -172,6 +191,8 @@ pub mod code {
         }
         pub trait IDebuggerEventData: Instance {
             fn getAddress(&self) -> Result<String>;
+            /// The address as dex address, `None` if it is not one, e.g. for native code.
+            fn getDexAddress(&self) -> Result<Option<DexAddress>>;
             fn getOutput(&self) -> Result<Vec<u8>>;
             fn getReturnValue(&self) -> Result<Box<dyn ITypedValue + '_>>;
             fn getThreadId(&self) -> Result<i64>;
@@ -277,6 +298,9 @@ pub mod code {
             fn getAddress(&self) -> Result<String> {
                 call!([String]self, "getAddress", "()Ljava/lang/String;", &[])
             }
+            fn getDexAddress(&self) -> Result<Option<DexAddress>> {
+                Ok(DexAddress::parse(&self.getAddress()?).ok())
+            }
 
             fn getOutput(&self) -> Result<Vec<u8>> {
                 let res = call!(self, "getOutput", "()[B", &[])?;
@@ -393,9 +417,14 @@ pub mod code {
         pub mod dex {
             static PACKAGE_NAME: &str =
                 "com/pnfsoftware/jeb/core/units/code/android/dex";
+            use crate::jeb::com::pnfsoftware::jeb::core::units::{
+                IDalvikInstruction, IDexClass, IDexField, IDexMethod, IDexUnit, JebDalvikInstruction,
+            };
 
             use crate::jeb::*;
 
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
             pub enum DexPoolType {
                 CallSite,
                 Class,
@@ -461,6 +490,760 @@ pub mod code {
                     }
                 }
             }
+
+            /// An encoded value of the dex file, e.g. the initial value of a static field.
+            #[derive(Debug, Clone, PartialEq)]
+            pub enum DexValue {
+                Byte(i8),
+                Short(i16),
+                Char(u16),
+                Int(i32),
+                Long(i64),
+                Float(f32),
+                Double(f64),
+                Boolean(bool),
+                StringIndex(i32),
+                TypeIndex(i32),
+                FieldIndex(i32),
+                MethodIndex(i32),
+                EnumFieldIndex(i32),
+                Array(Vec<DexValue>),
+                Null,
+                /// Method types, method handles and annotations, with their encoded value type.
+                Other(i32),
+            }
+
+            impl DexValue {
+                pub fn from_object(value: jni::objects::JObject) -> Result<'static, DexValue> {
+                    let env = get_vm!();
+                    let ty = env.call_method(value, "getType", "()I", &[])?.i()?;
+                    let get = |name: &str, sig: &str| env.call_method(value, name, sig, &[]);
+                    Ok(match ty {
+                        0x00 => DexValue::Byte(get("getByte", "()B")?.b()?),
+                        0x02 => DexValue::Short(get("getShort", "()S")?.s()?),
+                        0x03 => DexValue::Char(get("getChar", "()C")?.c()?),
+                        0x04 => DexValue::Int(get("getInt", "()I")?.i()?),
+                        0x06 => DexValue::Long(get("getLong", "()J")?.j()?),
+                        0x10 => DexValue::Float(get("getFloat", "()F")?.f()?),
+                        0x11 => DexValue::Double(get("getDouble", "()D")?.d()?),
+                        0x17 => DexValue::StringIndex(get("getStringIndex", "()I")?.i()?),
+                        0x18 => DexValue::TypeIndex(get("getTypeIndex", "()I")?.i()?),
+                        0x19 => DexValue::FieldIndex(get("getFieldIndex", "()I")?.i()?),
+                        0x1a => DexValue::MethodIndex(get("getMethodIndex", "()I")?.i()?),
+                        0x1b => DexValue::EnumFieldIndex(get("getEnumIndex", "()I")?.i()?),
+                        0x1c => {
+                            let list = get("getArray", "()Ljava/util/List;")?.l()?;
+                            let list = jni::objects::JList::from_env(&env, list)?;
+                            let mut values = vec![];
+                            for element in list.iter()? {
+                                values.push(DexValue::from_object(element)?);
+                            }
+                            DexValue::Array(values)
+                        }
+                        0x1e => DexValue::Null,
+                        0x1f => DexValue::Boolean(get("getBoolean", "()Z")?.z()?),
+                        ty => DexValue::Other(ty),
+                    })
+                }
+            }
+
+            bitflags::bitflags! {
+                /// The `access_flags` of a dex class, field or method.
+                pub struct AccessFlags: u32 {
+                    const PUBLIC = 0x1;
+                    const PRIVATE = 0x2;
+                    const PROTECTED = 0x4;
+                    const STATIC = 0x8;
+                    const FINAL = 0x10;
+                    const SYNCHRONIZED = 0x20;
+                    const VOLATILE = 0x40;
+                    const BRIDGE = 0x40;
+                    const TRANSIENT = 0x80;
+                    const VARARGS = 0x80;
+                    const NATIVE = 0x100;
+                    const INTERFACE = 0x200;
+                    const ABSTRACT = 0x400;
+                    const STRICT = 0x800;
+                    const SYNTHETIC = 0x1000;
+                    const ANNOTATION = 0x2000;
+                    const ENUM = 0x4000;
+                    const CONSTRUCTOR = 0x10000;
+                    const DECLARED_SYNCHRONIZED = 0x20000;
+                }
+            }
+
+            impl From<i32> for AccessFlags {
+                fn from(flags: i32) -> Self {
+                    AccessFlags::from_bits_truncate(flags as u32)
+                }
+            }
+
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub enum AnnotationVisibility {
+                Build,
+                Runtime,
+                System,
+                Unknown(i32),
+            }
+
+            impl From<i32> for AnnotationVisibility {
+                fn from(visibility: i32) -> Self {
+                    match visibility {
+                        0 => AnnotationVisibility::Build,
+                        1 => AnnotationVisibility::Runtime,
+                        2 => AnnotationVisibility::System,
+                        other => AnnotationVisibility::Unknown(other),
+                    }
+                }
+            }
+
+            /// An annotation with its type and element names resolved against the dex unit.
+            #[derive(Debug, Clone, PartialEq)]
+            pub struct DexAnnotation {
+                /// The type signature, e.g. `Ldalvik/annotation/InnerClass;`.
+                pub type_name: String,
+                pub visibility: AnnotationVisibility,
+                pub elements: Vec<DexAnnotationElement>,
+            }
+
+            impl DexAnnotation {
+                pub fn get_element(&self, name: &str) -> Option<&DexValue> {
+                    self.elements
+                        .iter()
+                        .find(|element| element.name == name)
+                        .map(|element| &element.value)
+                }
+            }
+
+            #[derive(Debug, Clone, PartialEq)]
+            pub struct DexAnnotationElement {
+                pub name: String,
+                pub value: DexValue,
+            }
+
+            macro_rules! dalvik_opcodes {
+                ($($variant:ident = $opcode:expr => $mnemonic:expr,)*) => {
+                    /// A Dalvik opcode. Opcodes JEB adds on top of the dex format, e.g. for odex
+                    /// files, are `Unknown`.
+                    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+                    pub enum DalvikOpcode {
+                        $($variant,)*
+                        Unknown(i32),
+                    }
+
+                    impl From<i32> for DalvikOpcode {
+                        fn from(opcode: i32) -> Self {
+                            match opcode {
+                                $($opcode => DalvikOpcode::$variant,)*
+                                other => DalvikOpcode::Unknown(other),
+                            }
+                        }
+                    }
+
+                    impl DalvikOpcode {
+                        pub fn opcode(&self) -> i32 {
+                            match self {
+                                $(DalvikOpcode::$variant => $opcode,)*
+                                DalvikOpcode::Unknown(opcode) => *opcode,
+                            }
+                        }
+                        pub fn mnemonic(&self) -> &'static str {
+                            match self {
+                                $(DalvikOpcode::$variant => $mnemonic,)*
+                                DalvikOpcode::Unknown(_) => "unknown",
+                            }
+                        }
+                    }
+                };
+            }
+
+            dalvik_opcodes! {
+                Nop = 0x00 => "nop",
+                Move = 0x01 => "move",
+                MoveFrom16 = 0x02 => "move/from16",
+                Move16 = 0x03 => "move/16",
+                MoveWide = 0x04 => "move-wide",
+                MoveWideFrom16 = 0x05 => "move-wide/from16",
+                MoveWide16 = 0x06 => "move-wide/16",
+                MoveObject = 0x07 => "move-object",
+                MoveObjectFrom16 = 0x08 => "move-object/from16",
+                MoveObject16 = 0x09 => "move-object/16",
+                MoveResult = 0x0a => "move-result",
+                MoveResultWide = 0x0b => "move-result-wide",
+                MoveResultObject = 0x0c => "move-result-object",
+                MoveException = 0x0d => "move-exception",
+                ReturnVoid = 0x0e => "return-void",
+                Return = 0x0f => "return",
+                ReturnWide = 0x10 => "return-wide",
+                ReturnObject = 0x11 => "return-object",
+                Const4 = 0x12 => "const/4",
+                Const16 = 0x13 => "const/16",
+                Const = 0x14 => "const",
+                ConstHigh16 = 0x15 => "const/high16",
+                ConstWide16 = 0x16 => "const-wide/16",
+                ConstWide32 = 0x17 => "const-wide/32",
+                ConstWide = 0x18 => "const-wide",
+                ConstWideHigh16 = 0x19 => "const-wide/high16",
+                ConstString = 0x1a => "const-string",
+                ConstStringJumbo = 0x1b => "const-string/jumbo",
+                ConstClass = 0x1c => "const-class",
+                MonitorEnter = 0x1d => "monitor-enter",
+                MonitorExit = 0x1e => "monitor-exit",
+                CheckCast = 0x1f => "check-cast",
+                InstanceOf = 0x20 => "instance-of",
+                ArrayLength = 0x21 => "array-length",
+                NewInstance = 0x22 => "new-instance",
+                NewArray = 0x23 => "new-array",
+                FilledNewArray = 0x24 => "filled-new-array",
+                FilledNewArrayRange = 0x25 => "filled-new-array/range",
+                FillArrayData = 0x26 => "fill-array-data",
+                Throw = 0x27 => "throw",
+                Goto = 0x28 => "goto",
+                Goto16 = 0x29 => "goto/16",
+                Goto32 = 0x2a => "goto/32",
+                PackedSwitch = 0x2b => "packed-switch",
+                SparseSwitch = 0x2c => "sparse-switch",
+                CmplFloat = 0x2d => "cmpl-float",
+                CmpgFloat = 0x2e => "cmpg-float",
+                CmplDouble = 0x2f => "cmpl-double",
+                CmpgDouble = 0x30 => "cmpg-double",
+                CmpLong = 0x31 => "cmp-long",
+                IfEq = 0x32 => "if-eq",
+                IfNe = 0x33 => "if-ne",
+                IfLt = 0x34 => "if-lt",
+                IfGe = 0x35 => "if-ge",
+                IfGt = 0x36 => "if-gt",
+                IfLe = 0x37 => "if-le",
+                IfEqz = 0x38 => "if-eqz",
+                IfNez = 0x39 => "if-nez",
+                IfLtz = 0x3a => "if-ltz",
+                IfGez = 0x3b => "if-gez",
+                IfGtz = 0x3c => "if-gtz",
+                IfLez = 0x3d => "if-lez",
+                Aget = 0x44 => "aget",
+                AgetWide = 0x45 => "aget-wide",
+                AgetObject = 0x46 => "aget-object",
+                AgetBoolean = 0x47 => "aget-boolean",
+                AgetByte = 0x48 => "aget-byte",
+                AgetChar = 0x49 => "aget-char",
+                AgetShort = 0x4a => "aget-short",
+                Aput = 0x4b => "aput",
+                AputWide = 0x4c => "aput-wide",
+                AputObject = 0x4d => "aput-object",
+                AputBoolean = 0x4e => "aput-boolean",
+                AputByte = 0x4f => "aput-byte",
+                AputChar = 0x50 => "aput-char",
+                AputShort = 0x51 => "aput-short",
+                Iget = 0x52 => "iget",
+                IgetWide = 0x53 => "iget-wide",
+                IgetObject = 0x54 => "iget-object",
+                IgetBoolean = 0x55 => "iget-boolean",
+                IgetByte = 0x56 => "iget-byte",
+                IgetChar = 0x57 => "iget-char",
+                IgetShort = 0x58 => "iget-short",
+                Iput = 0x59 => "iput",
+                IputWide = 0x5a => "iput-wide",
+                IputObject = 0x5b => "iput-object",
+                IputBoolean = 0x5c => "iput-boolean",
+                IputByte = 0x5d => "iput-byte",
+                IputChar = 0x5e => "iput-char",
+                IputShort = 0x5f => "iput-short",
+                Sget = 0x60 => "sget",
+                SgetWide = 0x61 => "sget-wide",
+                SgetObject = 0x62 => "sget-object",
+                SgetBoolean = 0x63 => "sget-boolean",
+                SgetByte = 0x64 => "sget-byte",
+                SgetChar = 0x65 => "sget-char",
+                SgetShort = 0x66 => "sget-short",
+                Sput = 0x67 => "sput",
+                SputWide = 0x68 => "sput-wide",
+                SputObject = 0x69 => "sput-object",
+                SputBoolean = 0x6a => "sput-boolean",
+                SputByte = 0x6b => "sput-byte",
+                SputChar = 0x6c => "sput-char",
+                SputShort = 0x6d => "sput-short",
+                InvokeVirtual = 0x6e => "invoke-virtual",
+                InvokeSuper = 0x6f => "invoke-super",
+                InvokeDirect = 0x70 => "invoke-direct",
+                InvokeStatic = 0x71 => "invoke-static",
+                InvokeInterface = 0x72 => "invoke-interface",
+                InvokeVirtualRange = 0x74 => "invoke-virtual/range",
+                InvokeSuperRange = 0x75 => "invoke-super/range",
+                InvokeDirectRange = 0x76 => "invoke-direct/range",
+                InvokeStaticRange = 0x77 => "invoke-static/range",
+                InvokeInterfaceRange = 0x78 => "invoke-interface/range",
+                NegInt = 0x7b => "neg-int",
+                NotInt = 0x7c => "not-int",
+                NegLong = 0x7d => "neg-long",
+                NotLong = 0x7e => "not-long",
+                NegFloat = 0x7f => "neg-float",
+                NegDouble = 0x80 => "neg-double",
+                IntToLong = 0x81 => "int-to-long",
+                IntToFloat = 0x82 => "int-to-float",
+                IntToDouble = 0x83 => "int-to-double",
+                LongToInt = 0x84 => "long-to-int",
+                LongToFloat = 0x85 => "long-to-float",
+                LongToDouble = 0x86 => "long-to-double",
+                FloatToInt = 0x87 => "float-to-int",
+                FloatToLong = 0x88 => "float-to-long",
+                FloatToDouble = 0x89 => "float-to-double",
+                DoubleToInt = 0x8a => "double-to-int",
+                DoubleToLong = 0x8b => "double-to-long",
+                DoubleToFloat = 0x8c => "double-to-float",
+                IntToByte = 0x8d => "int-to-byte",
+                IntToChar = 0x8e => "int-to-char",
+                IntToShort = 0x8f => "int-to-short",
+                AddInt = 0x90 => "add-int",
+                SubInt = 0x91 => "sub-int",
+                MulInt = 0x92 => "mul-int",
+                DivInt = 0x93 => "div-int",
+                RemInt = 0x94 => "rem-int",
+                AndInt = 0x95 => "and-int",
+                OrInt = 0x96 => "or-int",
+                XorInt = 0x97 => "xor-int",
+                ShlInt = 0x98 => "shl-int",
+                ShrInt = 0x99 => "shr-int",
+                UshrInt = 0x9a => "ushr-int",
+                AddLong = 0x9b => "add-long",
+                SubLong = 0x9c => "sub-long",
+                MulLong = 0x9d => "mul-long",
+                DivLong = 0x9e => "div-long",
+                RemLong = 0x9f => "rem-long",
+                AndLong = 0xa0 => "and-long",
+                OrLong = 0xa1 => "or-long",
+                XorLong = 0xa2 => "xor-long",
+                ShlLong = 0xa3 => "shl-long",
+                ShrLong = 0xa4 => "shr-long",
+                UshrLong = 0xa5 => "ushr-long",
+                AddFloat = 0xa6 => "add-float",
+                SubFloat = 0xa7 => "sub-float",
+                MulFloat = 0xa8 => "mul-float",
+                DivFloat = 0xa9 => "div-float",
+                RemFloat = 0xaa => "rem-float",
+                AddDouble = 0xab => "add-double",
+                SubDouble = 0xac => "sub-double",
+                MulDouble = 0xad => "mul-double",
+                DivDouble = 0xae => "div-double",
+                RemDouble = 0xaf => "rem-double",
+                AddInt2Addr = 0xb0 => "add-int/2addr",
+                SubInt2Addr = 0xb1 => "sub-int/2addr",
+                MulInt2Addr = 0xb2 => "mul-int/2addr",
+                DivInt2Addr = 0xb3 => "div-int/2addr",
+                RemInt2Addr = 0xb4 => "rem-int/2addr",
+                AndInt2Addr = 0xb5 => "and-int/2addr",
+                OrInt2Addr = 0xb6 => "or-int/2addr",
+                XorInt2Addr = 0xb7 => "xor-int/2addr",
+                ShlInt2Addr = 0xb8 => "shl-int/2addr",
+                ShrInt2Addr = 0xb9 => "shr-int/2addr",
+                UshrInt2Addr = 0xba => "ushr-int/2addr",
+                AddLong2Addr = 0xbb => "add-long/2addr",
+                SubLong2Addr = 0xbc => "sub-long/2addr",
+                MulLong2Addr = 0xbd => "mul-long/2addr",
+                DivLong2Addr = 0xbe => "div-long/2addr",
+                RemLong2Addr = 0xbf => "rem-long/2addr",
+                AndLong2Addr = 0xc0 => "and-long/2addr",
+                OrLong2Addr = 0xc1 => "or-long/2addr",
+                XorLong2Addr = 0xc2 => "xor-long/2addr",
+                ShlLong2Addr = 0xc3 => "shl-long/2addr",
+                ShrLong2Addr = 0xc4 => "shr-long/2addr",
+                UshrLong2Addr = 0xc5 => "ushr-long/2addr",
+                AddFloat2Addr = 0xc6 => "add-float/2addr",
+                SubFloat2Addr = 0xc7 => "sub-float/2addr",
+                MulFloat2Addr = 0xc8 => "mul-float/2addr",
+                DivFloat2Addr = 0xc9 => "div-float/2addr",
+                RemFloat2Addr = 0xca => "rem-float/2addr",
+                AddDouble2Addr = 0xcb => "add-double/2addr",
+                SubDouble2Addr = 0xcc => "sub-double/2addr",
+                MulDouble2Addr = 0xcd => "mul-double/2addr",
+                DivDouble2Addr = 0xce => "div-double/2addr",
+                RemDouble2Addr = 0xcf => "rem-double/2addr",
+                AddIntLit16 = 0xd0 => "add-int/lit16",
+                RsubInt = 0xd1 => "rsub-int",
+                MulIntLit16 = 0xd2 => "mul-int/lit16",
+                DivIntLit16 = 0xd3 => "div-int/lit16",
+                RemIntLit16 = 0xd4 => "rem-int/lit16",
+                AndIntLit16 = 0xd5 => "and-int/lit16",
+                OrIntLit16 = 0xd6 => "or-int/lit16",
+                XorIntLit16 = 0xd7 => "xor-int/lit16",
+                AddIntLit8 = 0xd8 => "add-int/lit8",
+                RsubIntLit8 = 0xd9 => "rsub-int/lit8",
+                MulIntLit8 = 0xda => "mul-int/lit8",
+                DivIntLit8 = 0xdb => "div-int/lit8",
+                RemIntLit8 = 0xdc => "rem-int/lit8",
+                AndIntLit8 = 0xdd => "and-int/lit8",
+                OrIntLit8 = 0xde => "or-int/lit8",
+                XorIntLit8 = 0xdf => "xor-int/lit8",
+                ShlIntLit8 = 0xe0 => "shl-int/lit8",
+                ShrIntLit8 = 0xe1 => "shr-int/lit8",
+                UshrIntLit8 = 0xe2 => "ushr-int/lit8",
+                InvokePolymorphic = 0xfa => "invoke-polymorphic",
+                InvokePolymorphicRange = 0xfb => "invoke-polymorphic/range",
+                InvokeCustom = 0xfc => "invoke-custom",
+                InvokeCustomRange = 0xfd => "invoke-custom/range",
+                ConstMethodHandle = 0xfe => "const-method-handle",
+                ConstMethodType = 0xff => "const-method-type",
+            }
+
+            impl DalvikOpcode {
+                /// The pool the index operand of the instruction refers to, `None` if the
+                /// instruction has no index operand.
+                pub fn index_pool_type(&self) -> Option<DexPoolType> {
+                    use DalvikOpcode::*;
+                    match self {
+                        ConstString | ConstStringJumbo => Some(DexPoolType::String),
+                        ConstClass | CheckCast | InstanceOf | NewInstance | NewArray
+                        | FilledNewArray | FilledNewArrayRange => Some(DexPoolType::Type),
+                        Iget | IgetWide | IgetObject | IgetBoolean | IgetByte | IgetChar
+                        | IgetShort | Iput | IputWide | IputObject | IputBoolean | IputByte
+                        | IputChar | IputShort | Sget | SgetWide | SgetObject | SgetBoolean
+                        | SgetByte | SgetChar | SgetShort | Sput | SputWide | SputObject
+                        | SputBoolean | SputByte | SputChar | SputShort => Some(DexPoolType::Field),
+                        InvokeVirtual | InvokeSuper | InvokeDirect | InvokeStatic
+                        | InvokeInterface | InvokeVirtualRange | InvokeSuperRange
+                        | InvokeDirectRange | InvokeStaticRange | InvokeInterfaceRange
+                        | InvokePolymorphic | InvokePolymorphicRange => Some(DexPoolType::Method),
+                        InvokeCustom | InvokeCustomRange => Some(DexPoolType::CallSite),
+                        ConstMethodHandle => Some(DexPoolType::MethodHandle),
+                        ConstMethodType => Some(DexPoolType::Prototype),
+                        _ => None,
+                    }
+                }
+                pub fn is_invoke(&self) -> bool {
+                    matches!(self.index_pool_type(), Some(DexPoolType::Method) | Some(DexPoolType::CallSite))
+                }
+            }
+
+            /// An operand of a Dalvik instruction.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub enum DalvikOperand {
+                Register(i64),
+                Immediate(i64),
+                /// An index into the pool given by `DalvikOpcode::index_pool_type`.
+                Index(i64),
+                /// A branch offset in 16-bit code units, relative to the instruction.
+                Branch(i64),
+                Other { kind: i32, value: i64 },
+            }
+
+            /// The payload of a `packed-switch` or `sparse-switch`.
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct DalvikSwitchData {
+                pub packed: bool,
+                /// The case keys with their targets, in 16-bit code units relative to the switch.
+                pub cases: Vec<(i32, i32)>,
+            }
+
+            /// A parsed dex address, as JEB writes them:
+            ///
+            /// - class: `Lcom/example/Foo;`
+            /// - method: `Lcom/example/Foo;->bar(I)V`
+            /// - field: `Lcom/example/Foo;->count:I`
+            /// - instruction: `Lcom/example/Foo;->bar(I)V+1Ah`, the offset in bytes and hex
+            #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+            pub struct DexAddress {
+                /// The class signature, e.g. `Lcom/example/Foo;`.
+                pub class: String,
+                /// The method or field name.
+                pub member: Option<String>,
+                /// The method descriptor like `(I)V`, or the field type like `I`.
+                pub descriptor: Option<String>,
+                pub offset: Option<i64>,
+            }
+
+            impl DexAddress {
+                pub fn parse(address: &str) -> Result<'static, DexAddress> {
+                    let invalid = || format!("invalid dex address: {}", address);
+                    let (member_address, offset) = match address.rfind('+') {
+                        Some(plus) => {
+                            let offset = &address[plus + 1..];
+                            let offset = offset
+                                .strip_suffix('h')
+                                .or_else(|| offset.strip_suffix('H'))
+                                .unwrap_or(offset);
+                            let offset = i64::from_str_radix(offset, 16).map_err(|_| invalid())?;
+                            (&address[..plus], Some(offset))
+                        }
+                        None => (address, None),
+                    };
+                    let (class, member) = match member_address.find("->") {
+                        Some(arrow) => (&member_address[..arrow], Some(&member_address[arrow + 2..])),
+                        None => (member_address, None),
+                    };
+                    if !class.starts_with(['L', '[']) || !class.ends_with(';') {
+                        return Err(invalid().into());
+                    }
+                    let (member, descriptor) = match member {
+                        Some(member) => {
+                            let split = member.find(['(', ':']).ok_or_else(invalid)?;
+                            let descriptor = if member[split..].starts_with(':') {
+                                &member[split + 1..]
+                            } else {
+                                &member[split..]
+                            };
+                            (Some(member[..split].to_string()), Some(descriptor.to_string()))
+                        }
+                        None => (None, None),
+                    };
+                    if offset.is_some() && !descriptor.as_deref().is_some_and(|d| d.starts_with('(')) {
+                        return Err(invalid().into());
+                    }
+                    Ok(DexAddress {
+                        class: class.to_string(),
+                        member,
+                        descriptor,
+                        offset,
+                    })
+                }
+
+                pub fn is_method(&self) -> bool {
+                    self.descriptor.as_deref().is_some_and(|d| d.starts_with('('))
+                }
+
+                pub fn is_field(&self) -> bool {
+                    self.descriptor.is_some() && !self.is_method()
+                }
+
+                /// The address of the method or field, without offset.
+                pub fn member_signature(&self) -> Option<String> {
+                    let mut address = self.clone();
+                    address.offset = None;
+                    address.member.as_ref()?;
+                    Some(address.to_string())
+                }
+
+                /// The address of the instruction at `offset` of this method.
+                pub fn with_offset(&self, offset: i64) -> DexAddress {
+                    DexAddress {
+                        offset: Some(offset),
+                        ..self.clone()
+                    }
+                }
+
+                pub fn resolve_class<'u>(&self, unit: &'u dyn IDexUnit<'u>) -> Result<'static, Box<dyn IDexClass<'u> + 'u>> {
+                    unit.getClassByName(&self.class)?
+                        .ok_or_else(|| format!("no class {}", self.class).into())
+                }
+
+                pub fn resolve_method<'u>(&self, unit: &'u dyn IDexUnit<'u>) -> Result<'static, Box<dyn IDexMethod<'u> + 'u>> {
+                    match self.member_signature() {
+                        Some(signature) if self.is_method() => unit
+                            .getMethodByName(&signature)?
+                            .ok_or_else(|| format!("no method {}", signature).into()),
+                        _ => Err(format!("{} is not a method address", self).into()),
+                    }
+                }
+
+                pub fn resolve_field<'u>(&self, unit: &'u dyn IDexUnit<'u>) -> Result<'static, Box<dyn IDexField<'u> + 'u>> {
+                    match self.member_signature() {
+                        Some(signature) if self.is_field() => unit
+                            .getFieldByName(&signature)?
+                            .ok_or_else(|| format!("no field {}", signature).into()),
+                        _ => Err(format!("{} is not a field address", self).into()),
+                    }
+                }
+
+                /// The instruction at the offset of a method address, `None` if no instruction
+                /// starts there.
+                pub fn resolve_instruction<'u>(
+                    &self,
+                    unit: &'u dyn IDexUnit<'u>,
+                ) -> Result<'static, Option<Box<dyn IDalvikInstruction<'u> + 'u>>> {
+                    let offset = self.offset.ok_or_else(|| format!("{} has no offset", self))?;
+                    let method = self.resolve_method(unit)?;
+                    for instruction in method.getDalvikInstructions()? {
+                        if instruction.getOffset()? == offset {
+                            let raw = instruction.get_obj()?.into_inner();
+                            return Ok(Some(Box::new(JebDalvikInstruction(jni::objects::JObject::from(raw).into()))));
+                        }
+                    }
+                    Ok(None)
+                }
+            }
+
+            impl std::fmt::Display for DexAddress {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    write!(f, "{}", self.class)?;
+                    if let Some(member) = &self.member {
+                        write!(f, "->{}", member)?;
+                        match &self.descriptor {
+                            Some(descriptor) if self.is_method() => write!(f, "{}", descriptor)?,
+                            Some(descriptor) => write!(f, ":{}", descriptor)?,
+                            None => {}
+                        }
+                    }
+                    if let Some(offset) = self.offset {
+                        write!(f, "+{:X}h", offset)?;
+                    }
+                    Ok(())
+                }
+            }
+
+            impl std::str::FromStr for DexAddress {
+                type Err = Box<dyn std::error::Error>;
+
+                fn from_str(address: &str) -> core::result::Result<Self, Self::Err> {
+                    DexAddress::parse(address)
+                }
+            }
+
+            #[cfg(test)]
+            mod tests {
+                use super::DexAddress;
+
+                #[test]
+                fn parse_class_method_field_and_instruction() {
+                    let class = DexAddress::parse("Lcom/example/Foo;").unwrap();
+                    assert_eq!(class.class, "Lcom/example/Foo;");
+                    assert_eq!((class.member, class.descriptor, class.offset), (None, None, None));
+
+                    let method = DexAddress::parse("Lcom/example/Foo;->bar(ILjava/lang/String;)V").unwrap();
+                    assert!(method.is_method() && !method.is_field());
+                    assert_eq!(method.member.as_deref(), Some("bar"));
+                    assert_eq!(method.descriptor.as_deref(), Some("(ILjava/lang/String;)V"));
+
+                    let field = DexAddress::parse("Lcom/example/Foo;->count:I").unwrap();
+                    assert!(field.is_field() && !field.is_method());
+                    assert_eq!(field.member.as_deref(), Some("count"));
+                    assert_eq!(field.descriptor.as_deref(), Some("I"));
+
+                    let instruction = DexAddress::parse("Lcom/example/Foo;->bar(I)V+1Ah").unwrap();
+                    assert_eq!(instruction.offset, Some(0x1a));
+                    assert_eq!(
+                        instruction.member_signature().as_deref(),
+                        Some("Lcom/example/Foo;->bar(I)V")
+                    );
+                }
+
+                #[test]
+                fn display_round_trip() {
+                    for address in &[
+                        "Lcom/example/Foo;",
+                        "[Lcom/example/Foo;",
+                        "Lcom/example/Foo$Inner;-><init>()V",
+                        "Lcom/example/Foo;->count:I",
+                        "Lcom/example/Foo;->bar(I)V+0h",
+                        "Lcom/example/Foo;->bar(I)V+1A2Bh",
+                    ] {
+                        let parsed = DexAddress::parse(address).unwrap();
+                        assert_eq!(parsed.to_string(), *address);
+                        assert_eq!(address.parse::<DexAddress>().unwrap(), parsed);
+                    }
+                    let method = DexAddress::parse("Lcom/example/Foo;->bar(I)V").unwrap();
+                    assert_eq!(method.with_offset(16).to_string(), "Lcom/example/Foo;->bar(I)V+10h");
+                }
+
+                #[test]
+                fn parse_rejects_invalid_addresses() {
+                    for address in &[
+                        "",
+                        "com/example/Foo",
+                        "Lcom/example/Foo",
+                        "Lcom/example/Foo;->bar",
+                        "Lcom/example/Foo;->count:I+4h",
+                        "Lcom/example/Foo;->bar(I)V+xyzh",
+                    ] {
+                        assert!(DexAddress::parse(address).is_err(), "{}", address);
+                    }
+                }
+            }
+
+            /// A try range of a code item with its handlers.
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct DexTryBlock {
+                pub start_address: i32,
+                pub end_address: i32,
+                pub handlers: Vec<DexCatchHandler>,
+            }
+
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct DexCatchHandler {
+                /// The caught type signature, `None` for a catch-all handler.
+                pub caught_type: Option<String>,
+                pub address: i32,
+            }
+
+            /// A local variable live at some offset, as declared by the debug info.
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct DexLocalVariable {
+                pub register: i32,
+                pub name: Option<String>,
+                pub type_signature: Option<String>,
+            }
+
+            /// The payload of a `fill-array-data`.
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct DalvikArrayData {
+                pub element_width: i32,
+                pub elements: Vec<Vec<u8>>,
+            }
+        }
+    }
+    pub mod asm {
+        package_name!("asm");
+        pub mod cfg {
+            package_name!("cfg");
+            use crate::jeb::com::pnfsoftware::jeb::core::units::{
+                IDalvikInstruction, JebDalvikInstruction,
+            };
+
+            jclass! {CFG, CFG_}
+            jclass! {BasicBlock, BasicBlock_}
+
+            /// The blocks in the `java.util.List` returned by the method `name` of `obj`.
+            fn block_list<'b>(
+                obj: jni::objects::JObject<'b>,
+                name: &str,
+            ) -> Result<'b, Vec<Box<BasicBlock<'b>>>> {
+                let env = get_vm!();
+                let list = env.call_method(obj, name, "()Ljava/util/List;", &[])?.l()?;
+                let mut blocks = vec![];
+                for element in jni::objects::JList::from_env(&env, list)?.iter()? {
+                    blocks.push(Box::new(BasicBlock(element.into())));
+                }
+                Ok(blocks)
+            }
+
+            impl<'a> CFG<'a> {
+                pub fn getBlocks(&self) -> Result<Vec<Box<BasicBlock<'_>>>> {
+                    block_list(self.get_obj()?, "getBlocks")
+                }
+                pub fn getEntryBlock(&self) -> Result<BasicBlock<'_>> {
+                    let res = call!(self, "getEntryBlock", "()Lcom.pnfsoftware.jeb.core.units.code.asm.cfg.BasicBlock;", &[])?;
+                    Ok(BasicBlock(res))
+                }
+                pub fn size(&self) -> Result<i32> {
+                    call!([i32]self, "size", "()I", &[])
+                }
+            }
+
+            impl<'a> BasicBlock<'a> {
+                pub fn getFirstAddress(&self) -> Result<i64> {
+                    call!([i64]self, "getFirstAddress", "()J", &[])
+                }
+                /// The address following the last instruction of the block.
+                pub fn getEndAddress(&self) -> Result<i64> {
+                    call!([i64]self, "getEndAddress", "()J", &[])
+                }
+                pub fn size(&self) -> Result<i32> {
+                    call!([i32]self, "size", "()I", &[])
+                }
+                pub fn get(&self, idx: i32) -> Result<Box<dyn IDalvikInstruction + '_>> {
+                    let res = call!(self, "get", "(I)Ljava/lang/Object;", &[idx.into()])?;
+                    Ok(Box::new(JebDalvikInstruction(res)))
+                }
+                pub fn getOutputs(&self) -> Result<Vec<Box<BasicBlock<'_>>>> {
+                    block_list(self.get_obj()?, "getOutputs")
+                }
+                pub fn getInputs(&self) -> Result<Vec<Box<BasicBlock<'_>>>> {
+                    block_list(self.get_obj()?, "getInputs")
+                }
+                pub fn getIrregularOutputs(&self) -> Result<Vec<Box<BasicBlock<'_>>>> {
+                    block_list(self.get_obj()?, "getIrregularOutputs")
+                }
+                pub fn getIrregularInputs(&self) -> Result<Vec<Box<BasicBlock<'_>>>> {
+                    block_list(self.get_obj()?, "getIrregularInputs")
+                }
+            }
         }
     }
 }
@@ -479,8 +1262,158 @@ pub trait IUnit<'a>: Instance {
         listener: Option<&dyn INativeEventListener>,
     ) -> Result<()>;
     fn getUnitProcessor(&self) -> Result<Box<dyn IUnitProcessor + '_>>;
+    /// Returns the parent unit, fails for top-level units whose parent is an artifact.
+    fn getParent(&self) -> Result<Box<dyn IUnit + '_>>;
+    fn getRealName(&self) -> Result<String>;
+    fn getUid(&self) -> Result<i64>;
+    fn getPropertyDefinitionManager(&self)
+        -> Result<Box<dyn IPropertyDefinitionManager + '_>>;
+    fn getPropertyManager(&self) -> Result<Box<dyn IPropertyManager + '_>>;
+    fn getInput(&self) -> Result<Box<dyn IInput + '_>>;
+    fn getCreationTimestamp(&self) -> Result<i64>;
+    fn canBePersisted(&self) -> Result<bool>;
+    fn getFormatter(&self) -> Result<Box<dyn IUnitFormatter + '_>>;
+    fn getNotificationManager(&self) -> Result<Box<dyn IUnitNotificationManager + '_>>;
+    /// The names of all units from the top-level unit down to this unit, separated by `/`.
+    /// This is the path `IRuntimeProject::findUnitByPath` expects.
+    fn getPath(&self) -> Result<String>;
+    /// Walks this unit and all its descendants in pre-order.
+    fn walk(&self) -> Result<UnitWalker<'_>>;
 }
 pub trait IUnitMarker<'a>: IUnit<'a> {}
+
+/// Pre-order iterator over a unit tree, yielding every unit together with its depth relative to
+/// the unit the walk started at.
+pub struct UnitWalker<'a> {
+    stack: Vec<(usize, JebUnit<'a>)>,
+    format_type: Option<String>,
+}
+
+impl<'a> UnitWalker<'a> {
+    pub fn new(root: &dyn IUnit) -> Result<'a, UnitWalker<'a>> {
+        let root = JebUnit(jni::objects::JObject::from(root.get_obj()?.into_inner()).into());
+        Ok(UnitWalker {
+            stack: vec![(0, root)],
+            format_type: None,
+        })
+    }
+
+    /// Only yields units of the given format type (e.g. `dex`), their children are still visited.
+    pub fn filter_format_type(mut self, format_type: &str) -> UnitWalker<'a> {
+        self.format_type = Some(format_type.to_string());
+        self
+    }
+}
+
+impl<'a> Iterator for UnitWalker<'a> {
+    type Item = Result<'a, (usize, Box<dyn IUnit<'a> + 'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((depth, unit)) = self.stack.pop() {
+            match unit.getChildren() {
+                Ok(children) => {
+                    for child in children.iter().rev() {
+                        match child.get_obj() {
+                            Ok(obj) => self.stack.push((
+                                depth + 1,
+                                JebUnit(jni::objects::JObject::from(obj.into_inner()).into()),
+                            )),
+                            Err(e) => return Some(Err(e)),
+                        }
+                    }
+                }
+                Err(e) => return Some(Err(e)),
+            }
+            if let Some(format_type) = &self.format_type {
+                match unit.getFormatType() {
+                    Ok(ty) if &ty == format_type => {}
+                    Ok(_) => continue,
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            return Some(Ok((depth, Box::new(unit))));
+        }
+        None
+    }
+}
+pub trait IUnitNotificationManager<'a>: Instance {
+    fn getNotifications(&self) -> Result<Vec<Box<dyn IUnitNotification + '_>>>;
+    fn getNotificationCount(&self) -> Result<i32>;
+}
+pub trait IUnitNotification<'a>: Instance {
+    fn getType(&self) -> Result<NotificationType>;
+    /// The severity of the notification type, higher is more severe.
+    fn getLevel(&self) -> Result<i32>;
+    fn getDescription(&self) -> Result<String>;
+    /// `None` if the notification is not tied to an address.
+    fn getAddress(&self) -> Result<Option<String>>;
+    fn getKey(&self) -> Result<String>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationType {
+    AreaOfInterest,
+    Corruption,
+    DeprecatedFeature,
+    Error,
+    Malicious,
+    PotentiallyHarmful,
+    UnsupportedFeature,
+    Warning,
+    Unknown,
+}
+
+impl NotificationType {
+    pub fn from_object(value: jni::objects::JObject) -> Result<'static, NotificationType> {
+        let env = get_vm!();
+        let name = env.call_method(value, "name", "()Ljava/lang/String;", &[])?.l()?;
+        if name.is_null() {
+            return Err("notification type without name".into());
+        }
+        let name: String = env.get_string(name.into())?.into();
+        Ok(match name.as_str() {
+            "AREA_OF_INTEREST" => NotificationType::AreaOfInterest,
+            "CORRUPTION" => NotificationType::Corruption,
+            "DEPRECATED_FEATURE" => NotificationType::DeprecatedFeature,
+            "ERROR" => NotificationType::Error,
+            "MALICIOUS" => NotificationType::Malicious,
+            "POTENTIALLY_HARMFUL" => NotificationType::PotentiallyHarmful,
+            "UNSUPPORTED_FEATURE" => NotificationType::UnsupportedFeature,
+            "WARNING" => NotificationType::Warning,
+            _ => NotificationType::Unknown,
+        })
+    }
+}
+
+/// A notification found in a unit tree, detached from the JVM so it can be put into reports.
+#[derive(Debug, Clone)]
+pub struct UnitNotificationEntry {
+    pub unit_path: String,
+    pub notification_type: NotificationType,
+    pub level: i32,
+    pub description: String,
+    pub address: Option<String>,
+}
+
+/// Collects the notifications of `root` and all its descendants.
+pub fn collect_notifications(root: &dyn IUnit) -> Result<'static, Vec<UnitNotificationEntry>> {
+    let mut entries = vec![];
+    for entry in root.walk()? {
+        let (_, unit) = entry?;
+        let unit_path = unit.getPath()?;
+        for notification in unit.getNotificationManager()?.getNotifications()? {
+            entries.push(UnitNotificationEntry {
+                unit_path: unit_path.clone(),
+                notification_type: notification.getType()?,
+                level: notification.getLevel()?,
+                description: notification.getDescription()?,
+                address: notification.getAddress()?,
+            });
+        }
+    }
+    Ok(entries)
+}
+
 pub trait IUnitProcessor<'a>: Instance {
     fn createDebugger(
         &self,
@@ -491,19 +1424,151 @@ pub trait IUnitProcessor<'a>: Instance {
 pub trait IUnitProcessorMarker {}
 pub trait IDexPackage<'a>: Instance {
     fn getName(&self) -> Result<String>;
-    fn getParentPackage(&mut self) -> Result<()>;
+    fn getSignature(&self, effective: bool) -> Result<String>;
+    /// The enclosing package, `None` for the root package.
+    fn getParentPackage(&self) -> Result<Option<Box<dyn IDexPackage + '_>>>;
     fn getChildrenPackages(&self) -> Result<Vec<Box<dyn IDexPackage + '_>>>;
     fn isRootPackage(&self) -> Result<bool>;
+    /// The classes of `unit` directly within the package.
+    ///
+    /// JEB has no per-package class list, so this scans every class of the unit and asks for its
+    /// package, two JNI calls per class. To look at several packages, group the classes once with
+    /// `classes_by_package`.
+    fn getClasses<'u>(&self, unit: &'u dyn IDexUnit<'u>) -> Result<Vec<Box<dyn IDexClass<'u> + 'u>>>;
+    /// The classes of `unit` within the package or any of its subpackages. Scans the unit like
+    /// `getClasses`.
+    fn getAllClasses<'u>(&self, unit: &'u dyn IDexUnit<'u>) -> Result<Vec<Box<dyn IDexClass<'u> + 'u>>>;
+    /// Scans the unit like `getClasses`, plus two JNI calls per class of the package.
+    fn getStatistics(&self, unit: &dyn IDexUnit) -> Result<PackageStatistics>;
+    /// The id JEB uses for the item in actions.
+    fn getItemId(&self) -> Result<i64>;
+    fn getAddress(&self) -> Result<String>;
 }
 pub trait IDexPackageMarker<'a>: IDexPackage<'a> {}
+
+/// The size of a package, subpackages included.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PackageStatistics {
+    pub packages: usize,
+    /// The classes directly within the package.
+    pub direct_classes: usize,
+    pub classes: usize,
+    pub methods: usize,
+    pub fields: usize,
+}
+
+/// The item ids of `package` and its subpackages.
+fn package_item_ids(package: &dyn IDexPackage, ids: &mut HashSet<i64>) -> Result<'static, ()> {
+    ids.insert(package.getItemId()?);
+    for child in package.getChildrenPackages()? {
+        package_item_ids(child.as_ref(), ids)?;
+    }
+    Ok(())
+}
+
+/// The classes of `unit` grouped by the item id of their package, see `IDexPackage::getItemId`.
+/// Reads the package of every class once, so it is the cheap way to look at many packages.
+pub fn classes_by_package<'u>(
+    unit: &'u dyn IDexUnit<'u>,
+) -> Result<'static, HashMap<i64, Vec<Box<dyn IDexClass<'u> + 'u>>>> {
+    let mut packages: HashMap<i64, Vec<_>> = HashMap::new();
+    for class in unit.getClasses()? {
+        let id = class.getPackage()?.getItemId()?;
+        packages.entry(id).or_default().push(class);
+    }
+    Ok(packages)
+}
+
+fn classes_in_packages<'u>(
+    unit: &'u dyn IDexUnit<'u>,
+    ids: &HashSet<i64>,
+) -> Result<'static, Vec<Box<dyn IDexClass<'u> + 'u>>> {
+    let mut classes = vec![];
+    for class in unit.getClasses()? {
+        if ids.contains(&class.getPackage()?.getItemId()?) {
+            classes.push(class);
+        }
+    }
+    Ok(classes)
+}
 pub trait IDexMethod<'a>: Instance {
     fn getSignature(&self, effective: bool) -> Result<String>;
     fn getIndex(&self) -> Result<i32>;
     fn getName(&self, effective: bool) -> Result<String>;
     fn getInstructions(&self) -> Result<Vec<Box<dyn IInstruction + '_>>>;
+    fn getDalvikInstructions(&self) -> Result<Vec<Box<dyn IDalvikInstruction + '_>>>;
+    /// Whether the method is defined in the dex unit, as opposed to a referenced external method.
+    fn isInternal(&self) -> Result<bool>;
+    fn getGenericFlags(&self) -> Result<i32>;
+    fn getClassType(&self) -> Result<Box<dyn IDexType + '_>>;
+    fn getPrototypeIndex(&self) -> Result<i32>;
+    fn getReturnType(&self) -> Result<Box<dyn IDexType + '_>>;
+    fn getParameterTypes(&self) -> Result<Vec<Box<dyn IDexType + '_>>>;
+    /// The definition of an internal method, `None` for external methods.
+    fn getData(&self) -> Result<Option<Box<dyn IDexMethodData + '_>>>;
+    /// The code of the method, `None` if it has none (abstract, native or external).
+    fn getCodeItem(&self) -> Result<Option<Box<dyn IDexCodeItem + '_>>>;
+    /// The access flags of an internal method, empty for external methods.
+    fn getAccessFlags(&self) -> Result<AccessFlags>;
+    fn isNative(&self) -> Result<bool>;
+    fn isAbstract(&self) -> Result<bool>;
+    fn isSynthetic(&self) -> Result<bool>;
+    /// The registers used by the method, `None` if it has no code (abstract, native or external).
+    fn getRegisterCount(&self) -> Result<Option<i32>>;
+    fn getAnnotations(&self, unit: &dyn IDexUnit) -> Result<Vec<DexAnnotation>>;
+    fn getItemId(&self) -> Result<i64>;
+    fn getAddress(&self) -> Result<String>;
 }
 pub trait IDexMethodMarker<'a>: IDexMethod<'a> {}
 
+pub trait IDexMethodData<'a>: Instance {
+    fn getAccessFlags(&self) -> Result<AccessFlags>;
+    fn getCodeItem(&self) -> Result<Option<Box<dyn IDexCodeItem + '_>>>;
+}
+pub trait IDexCodeItem<'a>: Instance {
+    fn getRegisterCount(&self) -> Result<i32>;
+    fn getInputArgumentCount(&self) -> Result<i32>;
+    fn getOutputArgumentCount(&self) -> Result<i32>;
+    fn getControlFlowGraph(&self) -> Result<CFG<'_>>;
+    fn getExceptionItems(&self) -> Result<Vec<Box<dyn IDexExceptionItem + '_>>>;
+    /// `None` if the code item carries no debug info.
+    fn getDebugInfo(&self) -> Result<Option<Box<dyn IDexDebugInfo + '_>>>;
+    /// The try blocks with their caught types resolved.
+    fn getTryBlocks(&self, unit: &dyn IDexUnit) -> Result<Vec<DexTryBlock>>;
+    /// The source line of the instruction at `offset`, `None` without line information.
+    fn getLineNumber(&self, offset: i32) -> Result<Option<i32>>;
+    /// The local variables live at `offset`, replaying the debug info up to there.
+    fn getLocalVariables(&self, unit: &dyn IDexUnit, offset: i32) -> Result<Vec<DexLocalVariable>>;
+}
+pub trait IDexExceptionItem<'a>: Instance {
+    fn getTryAddress(&self) -> Result<i32>;
+    fn getTryAddressEnd(&self) -> Result<i32>;
+    fn getHandlers(&self) -> Result<Vec<Box<dyn IDexExceptionHandler + '_>>>;
+}
+pub trait IDexExceptionHandler<'a>: Instance {
+    /// -1 for a catch-all handler.
+    fn getTypeIndex(&self) -> Result<i32>;
+    fn getAddress(&self) -> Result<i32>;
+}
+pub trait IDexDebugInfo<'a>: Instance {
+    fn getLineStart(&self) -> Result<i32>;
+    fn getParameterNameIndexes(&self) -> Result<Vec<i32>>;
+    fn getDebugLines(&self) -> Result<Vec<Box<dyn IDexDebugLine + '_>>>;
+}
+pub trait IDexDebugLine<'a>: Instance {
+    fn getOffset(&self) -> Result<i32>;
+    fn getLineNumber(&self) -> Result<i32>;
+    fn getVariables(&self) -> Result<Vec<Box<dyn IDexDebugVariable + '_>>>;
+}
+pub trait IDexDebugVariable<'a>: Instance {
+    fn getRegister(&self) -> Result<i32>;
+    fn getNameIndex(&self) -> Result<i32>;
+    fn getTypeIndex(&self) -> Result<i32>;
+    fn getTypeSignatureIndex(&self) -> Result<i32>;
+    fn isEndLocal(&self) -> Result<bool>;
+    fn isRestartLocal(&self) -> Result<bool>;
+}
+
 pub trait IInstruction {
     fn format(&self, context: Option<&dyn IUnit>) -> Result<String>;
     fn getSize(&self) -> Result<i32>;
@@ -511,14 +1576,87 @@ pub trait IInstruction {
 }
 pub trait IInstructionMarker {}
 
+pub trait IDalvikInstruction<'a>: IInstruction + Instance {
+    fn getOpcode(&self) -> Result<DalvikOpcode>;
+    fn getMnemonic(&self) -> Result<String>;
+    fn getOperands(&self) -> Result<Vec<DalvikOperand>>;
+    /// Resolves the index operand against the pool of `unit`, `None` if the instruction has none.
+    fn getIndexOperand<'u>(&self, unit: &'u dyn IDexUnit) -> Result<Option<DexPoolItem<'u>>>;
+    /// The offsets this instruction can branch to, switch cases included.
+    fn getBranchTargets(&self) -> Result<Vec<i64>>;
+    fn getSwitchData(&self) -> Result<Option<DalvikSwitchData>>;
+    fn getArrayData(&self) -> Result<Option<DalvikArrayData>>;
+}
+
+/// An item of one of the dex pools, as referenced by an instruction.
+pub enum DexPoolItem<'a> {
+    Method(Box<dyn IDexMethod<'a> + 'a>),
+    Field(Box<dyn IDexField<'a> + 'a>),
+    String(Box<dyn IDexString<'a> + 'a>),
+    Type(Box<dyn IDexType<'a> + 'a>),
+    Prototype(Box<dyn IDexPrototype<'a> + 'a>),
+    /// Call sites and method handles, with their pool index.
+    Other(DexPoolType, i32),
+}
+
 pub trait IDexClass<'a>: Instance {
     fn getMethods(&self) -> Result<Vec<Box<dyn IDexMethod + '_>>>;
     fn getName(&self) -> Result<String>;
     fn getPackage(&self) -> Result<Box<dyn IDexPackage + '_>>;
     fn getSignature(&self) -> Result<String>;
     fn getIndex(&self) -> Result<i32>;
+    fn isInternal(&self) -> Result<bool>;
+    fn getAccessFlags(&self) -> Result<AccessFlags>;
+    fn getClassType(&self) -> Result<Box<dyn IDexType + '_>>;
+    fn getSupertypes(&self) -> Result<Vec<Box<dyn IDexType + '_>>>;
+    fn getImplementedInterfaces(&self) -> Result<Vec<Box<dyn IDexType + '_>>>;
+    fn getFields(&self) -> Result<Vec<Box<dyn IDexField + '_>>>;
+    /// `None` if neither the class nor its members are annotated.
+    fn getAnnotationsDirectory(&self) -> Result<Option<Box<dyn IDexAnnotationsDirectory + '_>>>;
+    fn getAnnotations(&self, unit: &dyn IDexUnit) -> Result<Vec<DexAnnotation>>;
+    /// The signature of the class this class is declared in, read from the `EnclosingClass`
+    /// system annotation.
+    fn getEnclosingClass(&self, unit: &dyn IDexUnit) -> Result<Option<String>>;
+    /// The signatures of the classes declared in this class, read from the `MemberClasses`
+    /// system annotation.
+    fn getMemberClasses(&self, unit: &dyn IDexUnit) -> Result<Vec<String>>;
+    /// -1 if the class does not name its source file.
+    fn getSourceStringIndex(&self) -> Result<i32>;
+    fn getSourceFile(&self, unit: &dyn IDexUnit) -> Result<Option<String>>;
+    /// The index of the dex file defining the class, within the files of a merged unit.
+    fn getDexFileIndex(&self) -> Result<i32>;
+    fn getItemId(&self) -> Result<i64>;
+    fn getAddress(&self) -> Result<String>;
 }
 pub trait IDexClassMarker<'a>: IDexClass<'a> {}
+
+pub trait IDexAnnotationsDirectory<'a>: Instance {
+    fn getClassAnnotations(&self) -> Result<Vec<Box<dyn IDexAnnotationItem + '_>>>;
+    fn getFieldsAnnotations(&self) -> Result<Vec<Box<dyn IDexAnnotationForField + '_>>>;
+    fn getMethodsAnnotations(&self) -> Result<Vec<Box<dyn IDexAnnotationForMethod + '_>>>;
+}
+pub trait IDexAnnotationForField<'a>: Instance {
+    fn getFieldIndex(&self) -> Result<i32>;
+    fn getAnnotationItems(&self) -> Result<Vec<Box<dyn IDexAnnotationItem + '_>>>;
+}
+pub trait IDexAnnotationForMethod<'a>: Instance {
+    fn getMethodIndex(&self) -> Result<i32>;
+    fn getAnnotationItems(&self) -> Result<Vec<Box<dyn IDexAnnotationItem + '_>>>;
+}
+pub trait IDexAnnotationItem<'a>: Instance {
+    fn getVisibility(&self) -> Result<i32>;
+    fn getAnnotation(&self) -> Result<Box<dyn IDexAnnotation + '_>>;
+    /// Resolves the annotation type and element names.
+    fn resolve(&self, unit: &dyn IDexUnit) -> Result<DexAnnotation>;
+}
+pub trait IDexAnnotation<'a>: Instance {
+    fn getTypeIndex(&self) -> Result<i32>;
+    fn getElements(&self) -> Result<Vec<Box<dyn IDexAnnotationElement + '_>>>;
+}
+pub trait IDexAnnotationElement<'a>: Instance {
+    fn getNameIndex(&self) -> Result<i32>;
+    fn getValue(&self) -> Result<DexValue>;
+}
 pub trait IDexString<'a>: Instance {
     fn getIndex(&self) -> Result<i32>;
     fn getName(&self, effective: bool) -> Result<String>;
@@ -530,17 +1668,65 @@ pub trait IDexStringMarker<'a>: IDexString<'a> {}
 pub trait IDexUnit<'a>: Instance {
     fn getDisassembly(&self) -> Result<String>;
     fn getMethodByIndex(&self, idx: i32) -> Result<Box<dyn IDexMethod + '_>>;
+    /// The method with the signature `fqname`, `None` if the unit has no such method.
     fn getMethodByName(&self, fqname: &str)
-        -> Result<Box<dyn IDexMethod + '_>>;
+        -> Result<Option<Box<dyn IDexMethod + '_>>>;
+    /// The class with the signature `fqname`, `None` if the unit has no such class.
+    fn getClassByName(&self, fqname: &str) -> Result<Option<Box<dyn IDexClass + '_>>>;
+    /// The number of dex files making up the unit, more than one if JEB merged a multi-dex APK.
+    fn getDexFileCount(&self) -> Result<i32>;
+    /// The field with the signature `fqname`, `None` if the unit has no such field.
+    fn getFieldByName(&self, fqname: &str) -> Result<Option<Box<dyn IDexField + '_>>>;
     fn getMethods(&self) -> Result<Vec<Box<dyn IDexMethod + '_>>>;
     fn getClasses(&self) -> Result<Vec<Box<dyn IDexClass + '_>>>;
     fn getReferenceManager(&self)
         -> Result<Box<dyn IDexReferenceManager + '_>>;
     fn getString(&self, idx: i32) -> Result<Box<dyn IDexString + '_>>;
     fn getStrings(&self) -> Result<Vec<Box<dyn IDexString + '_>>>;
+    fn getFields(&self) -> Result<Vec<Box<dyn IDexField + '_>>>;
+    fn getField(&self, idx: i32) -> Result<Box<dyn IDexField + '_>>;
+    fn getTypes(&self) -> Result<Vec<Box<dyn IDexType + '_>>>;
+    fn getType(&self, idx: i32) -> Result<Box<dyn IDexType + '_>>;
+    fn getPrototypes(&self) -> Result<Vec<Box<dyn IDexPrototype + '_>>>;
+    fn getPrototype(&self, idx: i32) -> Result<Box<dyn IDexPrototype + '_>>;
+    fn getPackages(&self) -> Result<Vec<Box<dyn IDexPackage + '_>>>;
+    /// The package all other packages descend from.
+    fn getRootPackage(&self) -> Result<Box<dyn IDexPackage + '_>>;
 }
 pub trait IDexUnitMarker<'a>: IDexUnit<'a> {}
 
+pub trait IDexField<'a>: Instance {
+    fn getIndex(&self) -> Result<i32>;
+    fn getName(&self, effective: bool) -> Result<String>;
+    fn getSignature(&self, effective: bool) -> Result<String>;
+    fn getGenericFlags(&self) -> Result<i32>;
+    fn getClassTypeIndex(&self) -> Result<i32>;
+    fn getClassType(&self) -> Result<Box<dyn IDexType + '_>>;
+    fn getFieldTypeIndex(&self) -> Result<i32>;
+    fn getFieldType(&self) -> Result<Box<dyn IDexType + '_>>;
+    /// The initial value of a static field, `None` if the field has none.
+    fn getStaticInitializer(&self) -> Result<Option<DexValue>>;
+    fn isInternal(&self) -> Result<bool>;
+    /// The access flags of an internal field, empty for external fields.
+    fn getAccessFlags(&self) -> Result<AccessFlags>;
+    fn getItemId(&self) -> Result<i64>;
+    fn getAddress(&self) -> Result<String>;
+}
+pub trait IDexType<'a>: Instance {
+    fn getIndex(&self) -> Result<i32>;
+    fn getName(&self, effective: bool) -> Result<String>;
+    fn getSignature(&self, effective: bool) -> Result<String>;
+    /// The class defined in the dex unit for this type, `None` for external types.
+    fn getImplementingClass(&self) -> Result<Option<Box<dyn IDexClass + '_>>>;
+}
+pub trait IDexPrototype<'a>: Instance {
+    fn getIndex(&self) -> Result<i32>;
+    fn getShorty(&self) -> Result<String>;
+    fn getReturnTypeIndex(&self) -> Result<i32>;
+    fn getParameterTypeIndexes(&self) -> Result<Vec<i32>>;
+    fn generate(&self, effective: bool) -> Result<String>;
+}
+
 pub trait IDexReferenceManager<'a>: Instance {
     fn getReferences(
         &self,
@@ -551,6 +1737,7 @@ pub trait IDexReferenceManager<'a>: Instance {
 
 pub trait IDexAddress<'a>: Instance {
     fn getInternalAddress(&self) -> Result<String>;
+    fn getDexAddress(&self) -> Result<DexAddress>;
 }
 
 impl<'a> IDexPackage<'a> for JebDexPackage<'a> {
@@ -564,19 +1751,61 @@ impl<'a> IDexPackage<'a> for JebDexPackage<'a> {
             vec![]
         }
     }
-    fn getParentPackage(&mut self) -> Result<()> {
-        let res = call_object!(
-            self.0.l()?,
-            "getParentPackage",
-            "()Lcom.pnfsoftware.jeb.core.units.code.ICodePackage;",
-            &[]
-        )?;
-        self.0 = res;
-        Ok(())
+    fn getSignature(&self, effective: bool) -> Result<String> {
+        let args = [effective.into()];
+        call!([String]self, "getSignature", normalize!("(Z)Ljava.lang.String;"), &args)
+    }
+    fn getParentPackage(&self) -> Result<Option<Box<dyn IDexPackage + '_>>> {
+        let env = get_vm!();
+        let res = env
+            .call_method(self.get_obj()?, "getParentPackage", normalize!("()Lcom.pnfsoftware.jeb.core.units.code.ICodePackage;"), &[])?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(JebDexPackage(res.into()))))
     }
     fn isRootPackage(&self) -> Result<bool> {
         call!([Bool]self, "isRootPackage", normalize!("()Z"), &[])
     }
+    fn getClasses<'u>(&self, unit: &'u dyn IDexUnit<'u>) -> Result<Vec<Box<dyn IDexClass<'u> + 'u>>> {
+        let mut ids = HashSet::new();
+        ids.insert(self.getItemId()?);
+        classes_in_packages(unit, &ids)
+    }
+    fn getAllClasses<'u>(&self, unit: &'u dyn IDexUnit<'u>) -> Result<Vec<Box<dyn IDexClass<'u> + 'u>>> {
+        let mut ids = HashSet::new();
+        package_item_ids(self, &mut ids)?;
+        classes_in_packages(unit, &ids)
+    }
+    fn getStatistics(&self, unit: &dyn IDexUnit) -> Result<PackageStatistics> {
+        let id = self.getItemId()?;
+        let mut ids = HashSet::new();
+        package_item_ids(self, &mut ids)?;
+        let mut statistics = PackageStatistics {
+            packages: ids.len() - 1,
+            ..Default::default()
+        };
+        for class in unit.getClasses()? {
+            let package = class.getPackage()?.getItemId()?;
+            if !ids.contains(&package) {
+                continue;
+            }
+            if package == id {
+                statistics.direct_classes += 1;
+            }
+            statistics.classes += 1;
+            statistics.methods += class.getMethods()?.len();
+            statistics.fields += class.getFields()?.len();
+        }
+        Ok(statistics)
+    }
+    fn getItemId(&self) -> Result<i64> {
+        call!([i64]self, "getItemId", "()J", &[])
+    }
+    fn getAddress(&self) -> Result<String> {
+        call!([String]self, "getAddress", "()Ljava/lang/String;", &[])
+    }
 }
 
 impl<'a, T> IDexUnit<'a> for T
@@ -627,28 +1856,111 @@ where
     fn getMethodByName(
         &self,
         fqname: &str,
-    ) -> Result<Box<dyn IDexMethod + '_>> {
+    ) -> Result<Option<Box<dyn IDexMethod + '_>>> {
+        let env = get_vm!();
         let args = [jstring! {fqname}];
-        let res = call!(self, "getMethod", normalize!("(Ljava.lang.String;)Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexMethod;"), &args)?;
-        Ok(Box::new(JebDexMethod(res)))
+        let res = env
+            .call_method(self.get_obj()?, "getMethod", normalize!("(Ljava.lang.String;)Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexMethod;"), &args)?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(JebDexMethod(res.into()))))
+    }
+    fn getClassByName(&self, fqname: &str) -> Result<Option<Box<dyn IDexClass + '_>>> {
+        let env = get_vm!();
+        let args = [jstring! {fqname}];
+        let res = env
+            .call_method(self.get_obj()?, "getClass", normalize!("(Ljava.lang.String;)Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexClass;"), &args)?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(JebDexClass(res.into()))))
+    }
+    fn getDexFileCount(&self) -> Result<i32> {
+        let res = call!(self, "getDexFiles", "()Ljava/util/List;", &[])?;
+        call_object!([i32]res.l()?, "size", "()I", &[])
+    }
+    fn getFieldByName(&self, fqname: &str) -> Result<Option<Box<dyn IDexField + '_>>> {
+        let env = get_vm!();
+        let args = [jstring! {fqname}];
+        let res = env
+            .call_method(self.get_obj()?, "getField", normalize!("(Ljava.lang.String;)Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexField;"), &args)?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(JebDexField(res.into()))))
     }
-}
-impl<'a, T> IDexClass<'a> for T
-where
-    T: 'a + IDexClassMarker<'a> + Instance,
-{
     jcall! {
         Vec[normalize!("()Ljava/util/List;")]
-        [JebDexMethod]
-        fn getMethods() -> Vec<Box<dyn IDexMethod + '_>> {
+        [JebDexField]
+        fn getFields() -> Vec<Box<dyn IDexField + '_>> {
             vec![]
         }
     }
-    fn getIndex(&self) -> Result<i32> {
-        call!([i32]self, "getIndex", normalize!("()I"), &[])
-    }
-    fn getName(&self) -> Result<String> {
-        call!([String]self, "getName", normalize!("()Ljava.lang.String;"), &[])
+    fn getField(&self, idx: i32) -> Result<Box<dyn IDexField + '_>> {
+        let args = [idx.into()];
+        let res = call!(self, "getField", normalize!("(I)Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexField;"), &args)?;
+        Ok(Box::new(JebDexField(res)))
+    }
+    jcall! {
+        Vec[normalize!("()Ljava/util/List;")]
+        [JebDexType]
+        fn getTypes() -> Vec<Box<dyn IDexType + '_>> {
+            vec![]
+        }
+    }
+    fn getType(&self, idx: i32) -> Result<Box<dyn IDexType + '_>> {
+        let args = [idx.into()];
+        let res = call!(self, "getType", normalize!("(I)Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexType;"), &args)?;
+        Ok(Box::new(JebDexType(res)))
+    }
+    jcall! {
+        Vec[normalize!("()Ljava/util/List;")]
+        [JebDexPrototype]
+        fn getPrototypes() -> Vec<Box<dyn IDexPrototype + '_>> {
+            vec![]
+        }
+    }
+    fn getPrototype(&self, idx: i32) -> Result<Box<dyn IDexPrototype + '_>> {
+        let args = [idx.into()];
+        let res = call!(self, "getPrototype", normalize!("(I)Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexPrototype;"), &args)?;
+        Ok(Box::new(JebDexPrototype(res)))
+    }
+    jcall! {
+        Vec[normalize!("()Ljava/util/List;")]
+        [JebDexPackage]
+        fn getPackages() -> Vec<Box<dyn IDexPackage + '_>> {
+            vec![]
+        }
+    }
+    fn getRootPackage(&self) -> Result<Box<dyn IDexPackage + '_>> {
+        for package in self.getPackages()? {
+            if package.isRootPackage()? {
+                return Ok(package);
+            }
+        }
+        Err("the unit has no root package".into())
+    }
+}
+impl<'a, T> IDexClass<'a> for T
+where
+    T: 'a + IDexClassMarker<'a> + Instance,
+{
+    jcall! {
+        Vec[normalize!("()Ljava/util/List;")]
+        [JebDexMethod]
+        fn getMethods() -> Vec<Box<dyn IDexMethod + '_>> {
+            vec![]
+        }
+    }
+    fn getIndex(&self) -> Result<i32> {
+        call!([i32]self, "getIndex", normalize!("()I"), &[])
+    }
+    fn getName(&self) -> Result<String> {
+        call!([String]self, "getName", normalize!("()Ljava.lang.String;"), &[])
     }
     fn getSignature(&self) -> Result<String> {
         call!([String]self, "getSignature", normalize!("()Ljava.lang.String;"), &[])
@@ -662,6 +1974,123 @@ where
         )?;
         Ok(Box::new(JebDexPackage(res)))
     }
+    fn isInternal(&self) -> Result<bool> {
+        call!([Bool]self, "isInternal", "()Z", &[])
+    }
+    fn getAccessFlags(&self) -> Result<AccessFlags> {
+        let flags: Result<i32> = call!([i32]self, "getAccessFlags", "()I", &[]);
+        Ok(flags?.into())
+    }
+    fn getClassType(&self) -> Result<Box<dyn IDexType + '_>> {
+        let res = call!(self, "getClassType", "()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexType;", &[])?;
+        box_ok!(JebDexType(res))
+    }
+    jcall! {
+        Vec<IDexType>
+        [JebDexType]
+        fn getSupertypes()
+    }
+    jcall! {
+        Vec<IDexType>
+        [JebDexType]
+        fn getImplementedInterfaces()
+    }
+    jcall! {
+        Vec<IDexField>
+        [JebDexField]
+        fn getFields()
+    }
+    fn getAnnotationsDirectory(&self) -> Result<Option<Box<dyn IDexAnnotationsDirectory + '_>>> {
+        let env = get_vm!();
+        let res = env
+            .call_method(self.get_obj()?, "getAnnotationsDirectory", normalize!("()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexAnnotationsDirectory;"), &[])?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(JebDexAnnotationsDirectory(res.into()))))
+    }
+    fn getAnnotations(&self, unit: &dyn IDexUnit) -> Result<Vec<DexAnnotation>> {
+        match self.getAnnotationsDirectory()? {
+            Some(directory) => resolve_annotations(unit, directory.getClassAnnotations()?),
+            None => Ok(vec![]),
+        }
+    }
+    fn getEnclosingClass(&self, unit: &dyn IDexUnit) -> Result<Option<String>> {
+        for annotation in self.getAnnotations(unit)? {
+            if annotation.type_name == "Ldalvik/annotation/EnclosingClass;" {
+                if let Some(DexValue::TypeIndex(idx)) = annotation.get_element("value") {
+                    return Ok(Some(unit.getType(*idx)?.getSignature(false)?));
+                }
+            }
+        }
+        Ok(None)
+    }
+    fn getMemberClasses(&self, unit: &dyn IDexUnit) -> Result<Vec<String>> {
+        let mut member_classes = vec![];
+        for annotation in self.getAnnotations(unit)? {
+            if annotation.type_name == "Ldalvik/annotation/MemberClasses;" {
+                if let Some(DexValue::Array(values)) = annotation.get_element("value") {
+                    for value in values {
+                        if let DexValue::TypeIndex(idx) = value {
+                            member_classes.push(unit.getType(*idx)?.getSignature(false)?);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(member_classes)
+    }
+    fn getSourceStringIndex(&self) -> Result<i32> {
+        call!([i32]self, "getSourceStringIndex", "()I", &[])
+    }
+    fn getDexFileIndex(&self) -> Result<i32> {
+        call!([i32]self, "getDexFileIndex", "()I", &[])
+    }
+    fn getSourceFile(&self, unit: &dyn IDexUnit) -> Result<Option<String>> {
+        let idx = self.getSourceStringIndex()?;
+        if idx < 0 {
+            return Ok(None);
+        }
+        Ok(Some(unit.getString(idx)?.getValue()?))
+    }
+    fn getItemId(&self) -> Result<i64> {
+        call!([i64]self, "getItemId", "()J", &[])
+    }
+    fn getAddress(&self) -> Result<String> {
+        call!([String]self, "getAddress", "()Ljava/lang/String;", &[])
+    }
+}
+
+fn resolve_annotations(
+    unit: &dyn IDexUnit,
+    items: Vec<Box<dyn IDexAnnotationItem + '_>>,
+) -> Result<'static, Vec<DexAnnotation>> {
+    let mut annotations = vec![];
+    for item in items {
+        annotations.push(item.resolve(unit)?);
+    }
+    Ok(annotations)
+}
+
+/// The code item of a dex method, `None` if the method has no data or no code.
+fn code_item_object<'a>(
+    method: jni::objects::JObject<'a>,
+) -> Result<'a, Option<jni::objects::JObject<'a>>> {
+    let env = get_vm!();
+    let data = env
+        .call_method(method, "getData", normalize!("()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexMethodData;"), &[])?
+        .l()?;
+    if data.is_null() {
+        return Ok(None);
+    }
+    let code = env
+        .call_method(data, "getCodeItem", normalize!("()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexCodeItem;"), &[])?
+        .l()?;
+    if code.is_null() {
+        return Ok(None);
+    }
+    Ok(Some(code))
 }
 
 impl<'a, T> IDexMethod<'a> for T
@@ -683,9 +2112,423 @@ where
         [JebInstruction]
         fn getInstructions()
     }
+    fn getDalvikInstructions(&self) -> Result<Vec<Box<dyn IDalvikInstruction + '_>>> {
+        let env = get_vm!();
+        let list = env
+            .call_method(self.get_obj()?, "getInstructions", "()Ljava/util/List;", &[])?
+            .l()?;
+        let mut instructions: Vec<Box<dyn IDalvikInstruction + '_>> = vec![];
+        if list.is_null() {
+            return Ok(instructions);
+        }
+        for element in jni::objects::JList::from_env(&env, list)?.iter()? {
+            instructions.push(Box::new(JebDalvikInstruction(element.into())));
+        }
+        Ok(instructions)
+    }
+    fn isInternal(&self) -> Result<bool> {
+        call!([Bool]self, "isInternal", "()Z", &[])
+    }
+    fn getGenericFlags(&self) -> Result<i32> {
+        call!([i32]self, "getGenericFlags", "()I", &[])
+    }
+    fn getClassType(&self) -> Result<Box<dyn IDexType + '_>> {
+        let res = call!(self, "getClassType", "()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexType;", &[])?;
+        box_ok!(JebDexType(res))
+    }
+    fn getPrototypeIndex(&self) -> Result<i32> {
+        call!([i32]self, "getPrototypeIndex", "()I", &[])
+    }
+    fn getReturnType(&self) -> Result<Box<dyn IDexType + '_>> {
+        let res = call!(self, "getReturnType", "()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexType;", &[])?;
+        box_ok!(JebDexType(res))
+    }
+    jcall! {
+        Vec<IDexType>
+        [JebDexType]
+        fn getParameterTypes()
+    }
+    fn getData(&self) -> Result<Option<Box<dyn IDexMethodData + '_>>> {
+        let env = get_vm!();
+        let res = env
+            .call_method(self.get_obj()?, "getData", normalize!("()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexMethodData;"), &[])?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(JebDexMethodData(res.into()))))
+    }
+    fn getCodeItem(&self) -> Result<Option<Box<dyn IDexCodeItem + '_>>> {
+        match code_item_object(self.get_obj()?)? {
+            Some(code) => Ok(Some(Box::new(JebDexCodeItem(code.into())))),
+            None => Ok(None),
+        }
+    }
+    fn getAccessFlags(&self) -> Result<AccessFlags> {
+        match self.getData()? {
+            Some(data) => data.getAccessFlags(),
+            None => Ok(AccessFlags::empty()),
+        }
+    }
+    fn isNative(&self) -> Result<bool> {
+        Ok(self.getAccessFlags()?.contains(AccessFlags::NATIVE))
+    }
+    fn isAbstract(&self) -> Result<bool> {
+        Ok(self.getAccessFlags()?.contains(AccessFlags::ABSTRACT))
+    }
+    fn isSynthetic(&self) -> Result<bool> {
+        Ok(self.getAccessFlags()?.contains(AccessFlags::SYNTHETIC))
+    }
+    fn getRegisterCount(&self) -> Result<Option<i32>> {
+        match code_item_object(self.get_obj()?)? {
+            Some(code) => Ok(Some(JebDexCodeItem(code.into()).getRegisterCount()?)),
+            None => Ok(None),
+        }
+    }
+    fn getAnnotations(&self, unit: &dyn IDexUnit) -> Result<Vec<DexAnnotation>> {
+        let class_type = self.getClassType()?;
+        let class = match class_type.getImplementingClass()? {
+            Some(class) => class,
+            // external methods have no annotations in this unit
+            None => return Ok(vec![]),
+        };
+        let directory = match class.getAnnotationsDirectory()? {
+            Some(directory) => directory,
+            None => return Ok(vec![]),
+        };
+        let index = self.getIndex()?;
+        for method_annotations in directory.getMethodsAnnotations()? {
+            if method_annotations.getMethodIndex()? == index {
+                return resolve_annotations(unit, method_annotations.getAnnotationItems()?);
+            }
+        }
+        Ok(vec![])
+    }
+    fn getItemId(&self) -> Result<i64> {
+        call!([i64]self, "getItemId", "()J", &[])
+    }
+    fn getAddress(&self) -> Result<String> {
+        call!([String]self, "getAddress", "()Ljava/lang/String;", &[])
+    }
+}
+
+impl<'a> IDexMethodData<'a> for JebDexMethodData<'a> {
+    fn getAccessFlags(&self) -> Result<AccessFlags> {
+        let flags: Result<i32> = call!([i32]self, "getAccessFlags", "()I", &[]);
+        Ok(flags?.into())
+    }
+    fn getCodeItem(&self) -> Result<Option<Box<dyn IDexCodeItem + '_>>> {
+        let env = get_vm!();
+        let res = env
+            .call_method(self.get_obj()?, "getCodeItem", normalize!("()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexCodeItem;"), &[])?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(JebDexCodeItem(res.into()))))
+    }
 }
 
-impl<'a> IInstruction for JebInstruction<'a> {
+impl<'a> IDexCodeItem<'a> for JebDexCodeItem<'a> {
+    fn getRegisterCount(&self) -> Result<i32> {
+        call!([i32]self, "getRegisterCount", "()I", &[])
+    }
+    fn getInputArgumentCount(&self) -> Result<i32> {
+        call!([i32]self, "getInputArgumentCount", "()I", &[])
+    }
+    fn getOutputArgumentCount(&self) -> Result<i32> {
+        call!([i32]self, "getOutputArgumentCount", "()I", &[])
+    }
+    fn getControlFlowGraph(&self) -> Result<CFG<'_>> {
+        let res = call!(self, "getControlFlowGraph", "()Lcom.pnfsoftware.jeb.core.units.code.asm.cfg.CFG;", &[])?;
+        Ok(CFG(res))
+    }
+    jcall! {
+        Vec<IDexExceptionItem>
+        [JebDexExceptionItem]
+        fn getExceptionItems()
+    }
+    fn getDebugInfo(&self) -> Result<Option<Box<dyn IDexDebugInfo + '_>>> {
+        let env = get_vm!();
+        let res = env
+            .call_method(self.get_obj()?, "getDebugInfo", normalize!("()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexDebugInfo;"), &[])?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(JebDexDebugInfo(res.into()))))
+    }
+    fn getTryBlocks(&self, unit: &dyn IDexUnit) -> Result<Vec<DexTryBlock>> {
+        let mut try_blocks = vec![];
+        for item in self.getExceptionItems()? {
+            let mut handlers = vec![];
+            for handler in item.getHandlers()? {
+                let type_index = handler.getTypeIndex()?;
+                let caught_type = if type_index < 0 {
+                    None
+                } else {
+                    Some(unit.getType(type_index)?.getSignature(false)?)
+                };
+                handlers.push(DexCatchHandler {
+                    caught_type,
+                    address: handler.getAddress()?,
+                });
+            }
+            try_blocks.push(DexTryBlock {
+                start_address: item.getTryAddress()?,
+                end_address: item.getTryAddressEnd()?,
+                handlers,
+            });
+        }
+        Ok(try_blocks)
+    }
+    fn getLineNumber(&self, offset: i32) -> Result<Option<i32>> {
+        let debug_info = match self.getDebugInfo()? {
+            Some(debug_info) => debug_info,
+            None => return Ok(None),
+        };
+        let mut line_number = None;
+        for line in debug_info.getDebugLines()? {
+            if line.getOffset()? > offset {
+                break;
+            }
+            line_number = Some(line.getLineNumber()?);
+        }
+        Ok(line_number)
+    }
+    fn getLocalVariables(&self, unit: &dyn IDexUnit, offset: i32) -> Result<Vec<DexLocalVariable>> {
+        let debug_info = match self.getDebugInfo()? {
+            Some(debug_info) => debug_info,
+            None => return Ok(vec![]),
+        };
+        let string_at = |idx: i32| -> Result<'static, Option<String>> {
+            if idx < 0 {
+                Ok(None)
+            } else {
+                Ok(Some(unit.getString(idx)?.getValue()?))
+            }
+        };
+        let type_at = |idx: i32| -> Result<'static, Option<String>> {
+            if idx < 0 {
+                Ok(None)
+            } else {
+                Ok(Some(unit.getType(idx)?.getSignature(false)?))
+            }
+        };
+        // the variables ever declared per register, to restart them after they ended
+        let mut declared: HashMap<i32, DexLocalVariable> = HashMap::new();
+        let mut live: HashMap<i32, DexLocalVariable> = HashMap::new();
+        for line in debug_info.getDebugLines()? {
+            if line.getOffset()? > offset {
+                break;
+            }
+            for variable in line.getVariables()? {
+                let register = variable.getRegister()?;
+                if variable.isEndLocal()? {
+                    live.remove(&register);
+                } else if variable.isRestartLocal()? {
+                    if let Some(local) = declared.get(&register) {
+                        live.insert(register, local.clone());
+                    }
+                } else {
+                    let local = DexLocalVariable {
+                        register,
+                        name: string_at(variable.getNameIndex()?)?,
+                        type_signature: type_at(variable.getTypeIndex()?)?,
+                    };
+                    declared.insert(register, local.clone());
+                    live.insert(register, local);
+                }
+            }
+        }
+        let mut locals: Vec<DexLocalVariable> = live.into_values().collect();
+        locals.sort_by_key(|local| local.register);
+        Ok(locals)
+    }
+}
+
+impl<'a> IDexExceptionItem<'a> for JebDexExceptionItem<'a> {
+    fn getTryAddress(&self) -> Result<i32> {
+        call!([i32]self, "getTryAddress", "()I", &[])
+    }
+    fn getTryAddressEnd(&self) -> Result<i32> {
+        call!([i32]self, "getTryAddressEnd", "()I", &[])
+    }
+    jcall! {
+        Vec<IDexExceptionHandler>
+        [JebDexExceptionHandler]
+        fn getHandlers()
+    }
+}
+
+impl<'a> IDexExceptionHandler<'a> for JebDexExceptionHandler<'a> {
+    fn getTypeIndex(&self) -> Result<i32> {
+        call!([i32]self, "getTypeIndex", "()I", &[])
+    }
+    fn getAddress(&self) -> Result<i32> {
+        call!([i32]self, "getAddress", "()I", &[])
+    }
+}
+
+impl<'a> IDexDebugInfo<'a> for JebDexDebugInfo<'a> {
+    fn getLineStart(&self) -> Result<i32> {
+        call!([i32]self, "getLineStart", "()I", &[])
+    }
+    fn getParameterNameIndexes(&self) -> Result<Vec<i32>> {
+        let res = call!(self, "getParameterNameIndexes", "()[I", &[])?;
+        let env = get_vm!();
+        let array = res.l()?.into_inner();
+        let mut indexes = vec![0; env.get_array_length(array)? as usize];
+        env.get_int_array_region(array, 0, &mut indexes)?;
+        Ok(indexes)
+    }
+    fn getDebugLines(&self) -> Result<Vec<Box<dyn IDexDebugLine + '_>>> {
+        let res = call!(self, "getDebugLines", "()[Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexDebugLine;", &[])?;
+        let env = get_vm!();
+        let array = res.l()?.into_inner();
+        let mut lines: Vec<Box<dyn IDexDebugLine + '_>> = vec![];
+        for i in 0..env.get_array_length(array)? {
+            lines.push(Box::new(JebDexDebugLine(env.get_object_array_element(array, i)?.into())));
+        }
+        Ok(lines)
+    }
+}
+
+impl<'a> IDexDebugLine<'a> for JebDexDebugLine<'a> {
+    fn getOffset(&self) -> Result<i32> {
+        call!([i32]self, "getOffset", "()I", &[])
+    }
+    fn getLineNumber(&self) -> Result<i32> {
+        call!([i32]self, "getLineNumber", "()I", &[])
+    }
+    fn getVariables(&self) -> Result<Vec<Box<dyn IDexDebugVariable + '_>>> {
+        let env = get_vm!();
+        let array = env
+            .call_method(self.get_obj()?, "getVariables", normalize!("()[Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexDebugVariable;"), &[])?
+            .l()?;
+        let mut variables: Vec<Box<dyn IDexDebugVariable + '_>> = vec![];
+        if array.is_null() {
+            return Ok(variables);
+        }
+        let array = array.into_inner();
+        for i in 0..env.get_array_length(array)? {
+            variables.push(Box::new(JebDexDebugVariable(env.get_object_array_element(array, i)?.into())));
+        }
+        Ok(variables)
+    }
+}
+
+impl<'a> IDexDebugVariable<'a> for JebDexDebugVariable<'a> {
+    fn getRegister(&self) -> Result<i32> {
+        call!([i32]self, "getRegister", "()I", &[])
+    }
+    fn getNameIndex(&self) -> Result<i32> {
+        call!([i32]self, "getNameIndex", "()I", &[])
+    }
+    fn getTypeIndex(&self) -> Result<i32> {
+        call!([i32]self, "getTypeIndex", "()I", &[])
+    }
+    fn getTypeSignatureIndex(&self) -> Result<i32> {
+        call!([i32]self, "getTypeSignatureIndex", "()I", &[])
+    }
+    fn isEndLocal(&self) -> Result<bool> {
+        call!([Bool]self, "isEndLocal", "()Z", &[])
+    }
+    fn isRestartLocal(&self) -> Result<bool> {
+        call!([Bool]self, "isRestartLocal", "()Z", &[])
+    }
+}
+
+impl<'a> IDexAnnotationsDirectory<'a> for JebDexAnnotationsDirectory<'a> {
+    jcall! {
+        Vec<IDexAnnotationItem>
+        [JebDexAnnotationItem]
+        fn getClassAnnotations()
+    }
+    jcall! {
+        Vec<IDexAnnotationForField>
+        [JebDexAnnotationForField]
+        fn getFieldsAnnotations()
+    }
+    jcall! {
+        Vec<IDexAnnotationForMethod>
+        [JebDexAnnotationForMethod]
+        fn getMethodsAnnotations()
+    }
+}
+
+impl<'a> IDexAnnotationForField<'a> for JebDexAnnotationForField<'a> {
+    fn getFieldIndex(&self) -> Result<i32> {
+        call!([i32]self, "getFieldIndex", "()I", &[])
+    }
+    jcall! {
+        Vec<IDexAnnotationItem>
+        [JebDexAnnotationItem]
+        fn getAnnotationItems()
+    }
+}
+
+impl<'a> IDexAnnotationForMethod<'a> for JebDexAnnotationForMethod<'a> {
+    fn getMethodIndex(&self) -> Result<i32> {
+        call!([i32]self, "getMethodIndex", "()I", &[])
+    }
+    jcall! {
+        Vec<IDexAnnotationItem>
+        [JebDexAnnotationItem]
+        fn getAnnotationItems()
+    }
+}
+
+impl<'a> IDexAnnotationItem<'a> for JebDexAnnotationItem<'a> {
+    fn getVisibility(&self) -> Result<i32> {
+        call!([i32]self, "getVisibility", "()I", &[])
+    }
+    fn getAnnotation(&self) -> Result<Box<dyn IDexAnnotation + '_>> {
+        let res = call!(self, "getAnnotation", "()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexAnnotation;", &[])?;
+        box_ok!(JebDexAnnotation(res))
+    }
+    fn resolve(&self, unit: &dyn IDexUnit) -> Result<DexAnnotation> {
+        let annotation = self.getAnnotation()?;
+        let mut elements = vec![];
+        for element in annotation.getElements()? {
+            elements.push(DexAnnotationElement {
+                name: unit.getString(element.getNameIndex()?)?.getValue()?,
+                value: element.getValue()?,
+            });
+        }
+        Ok(DexAnnotation {
+            type_name: unit.getType(annotation.getTypeIndex()?)?.getSignature(false)?,
+            visibility: self.getVisibility()?.into(),
+            elements,
+        })
+    }
+}
+
+impl<'a> IDexAnnotation<'a> for JebDexAnnotation<'a> {
+    fn getTypeIndex(&self) -> Result<i32> {
+        call!([i32]self, "getTypeIndex", "()I", &[])
+    }
+    jcall! {
+        Vec<IDexAnnotationElement>
+        [JebDexAnnotationElement]
+        fn getElements()
+    }
+}
+
+impl<'a> IDexAnnotationElement<'a> for JebDexAnnotationElement<'a> {
+    fn getNameIndex(&self) -> Result<i32> {
+        call!([i32]self, "getNameIndex", "()I", &[])
+    }
+    fn getValue(&self) -> Result<DexValue> {
+        let res = call!(self, "getValue", "()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexValue;", &[])?;
+        DexValue::from_object(res.l()?)
+    }
+}
+
+impl<T> IInstruction for T
+where
+    T: IInstructionMarker + Instance,
+{
     fn format(&self, context: Option<&dyn IUnit>) -> Result<String> {
         let args = jargs!(context);
         call!([String]self, "format", "(Ljava/lang/Object;)Ljava/lang/String;", &args)
@@ -698,6 +2541,229 @@ impl<'a> IInstruction for JebInstruction<'a> {
     }
 }
 
+impl<'a> IDalvikInstruction<'a> for JebDalvikInstruction<'a> {
+    fn getOpcode(&self) -> Result<DalvikOpcode> {
+        let opcode: Result<i32> = call!([i32]self, "getOpcode", "()I", &[]);
+        Ok(opcode?.into())
+    }
+    fn getMnemonic(&self) -> Result<String> {
+        call!([String]self, "getMnemonic", "()Ljava/lang/String;", &[])
+    }
+    fn getOperands(&self) -> Result<Vec<DalvikOperand>> {
+        let env = get_vm!();
+        let instruction_class = "com.pnfsoftware.jeb.core.units.code.android.dex.IDalvikInstruction".replace(".", "/");
+        let kind = |name: &str| env.get_static_field(instruction_class.as_str(), name, "I").and_then(|v| v.i());
+        let (reg, imm, idx, bra) = (kind("TYPE_REG")?, kind("TYPE_IMM")?, kind("TYPE_IDX")?, kind("TYPE_BRA")?);
+
+        let parameters = call!(self, "getParameters", "()[Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDalvikInstructionParameter;", &[])?;
+        let parameters = parameters.l()?.into_inner();
+        let mut operands = vec![];
+        for i in 0..env.get_array_length(parameters)? {
+            let parameter = env.get_object_array_element(parameters, i)?;
+            let parameter_kind = env.call_method(parameter, "getType", "()I", &[])?.i()?;
+            let value = env.call_method(parameter, "getValue", "()J", &[])?.j()?;
+            operands.push(match parameter_kind {
+                k if k == reg => DalvikOperand::Register(value),
+                k if k == imm => DalvikOperand::Immediate(value),
+                k if k == idx => DalvikOperand::Index(value),
+                k if k == bra => DalvikOperand::Branch(value),
+                other => DalvikOperand::Other { kind: other, value },
+            });
+        }
+        Ok(operands)
+    }
+    fn getIndexOperand<'u>(&self, unit: &'u dyn IDexUnit) -> Result<Option<DexPoolItem<'u>>> {
+        let pool_type = match self.getOpcode()?.index_pool_type() {
+            Some(pool_type) => pool_type,
+            None => return Ok(None),
+        };
+        let index = self.getOperands()?.into_iter().find_map(|operand| match operand {
+            DalvikOperand::Index(index) => Some(index as i32),
+            _ => None,
+        });
+        let index = match index {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        Ok(Some(match pool_type {
+            DexPoolType::Method => DexPoolItem::Method(unit.getMethodByIndex(index)?),
+            DexPoolType::Field => DexPoolItem::Field(unit.getField(index)?),
+            DexPoolType::String => DexPoolItem::String(unit.getString(index)?),
+            DexPoolType::Type => DexPoolItem::Type(unit.getType(index)?),
+            DexPoolType::Prototype => DexPoolItem::Prototype(unit.getPrototype(index)?),
+            other => DexPoolItem::Other(other, index),
+        }))
+    }
+    fn getBranchTargets(&self) -> Result<Vec<i64>> {
+        let offset = self.getOffset()?;
+        let mut targets = vec![];
+        for operand in self.getOperands()? {
+            if let DalvikOperand::Branch(relative) = operand {
+                targets.push(offset + relative * 2);
+            }
+        }
+        if let Some(switch_data) = self.getSwitchData()? {
+            for (_, relative) in switch_data.cases {
+                targets.push(offset + relative as i64 * 2);
+            }
+        }
+        Ok(targets)
+    }
+    fn getSwitchData(&self) -> Result<Option<DalvikSwitchData>> {
+        let env = get_vm!();
+        let data = env
+            .call_method(self.get_obj()?, "getSwitchData", normalize!("()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDalvikInstructionSwitchData;"), &[])?
+            .l()?;
+        if data.is_null() {
+            return Ok(None);
+        }
+        let packed = env.call_method(data, "isPackedSwitch", "()Z", &[])?.z()?;
+        let elements = env.call_method(data, "getElements", "()[[I", &[])?.l()?.into_inner();
+        let mut cases = vec![];
+        for i in 0..env.get_array_length(elements)? {
+            let element = env.get_object_array_element(elements, i)?.into_inner();
+            let mut case = [0; 2];
+            env.get_int_array_region(element, 0, &mut case)?;
+            cases.push((case[0], case[1]));
+        }
+        Ok(Some(DalvikSwitchData { packed, cases }))
+    }
+    fn getArrayData(&self) -> Result<Option<DalvikArrayData>> {
+        let env = get_vm!();
+        let data = env
+            .call_method(self.get_obj()?, "getArrayData", normalize!("()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDalvikInstructionArrayData;"), &[])?
+            .l()?;
+        if data.is_null() {
+            return Ok(None);
+        }
+        let element_width = env.call_method(data, "getElementWidth", "()I", &[])?.i()?;
+        let array = env.call_method(data, "getElements", "()[[B", &[])?.l()?.into_inner();
+        let mut elements = vec![];
+        for i in 0..env.get_array_length(array)? {
+            let element = env.get_object_array_element(array, i)?.into_inner();
+            elements.push(env.convert_byte_array(element)?);
+        }
+        Ok(Some(DalvikArrayData {
+            element_width,
+            elements,
+        }))
+    }
+}
+
+pub trait IInteractiveUnit<'a>: IUnit<'a> {
+    fn canExecuteAction(&self, context: &ActionContext) -> Result<bool>;
+    /// Fills `data` with the current state, e.g. the current name for a rename.
+    fn prepareExecution(&self, context: &ActionContext, data: &dyn IActionData) -> Result<bool>;
+    fn executeAction(&self, context: &ActionContext, data: &dyn IActionData) -> Result<bool>;
+    fn renameClass(&self, class: &dyn IDexClass, name: &str) -> Result<bool>;
+    fn renameMethod(&self, method: &dyn IDexMethod, name: &str) -> Result<bool>;
+    fn renameField(&self, field: &dyn IDexField, name: &str) -> Result<bool>;
+    fn renamePackage(&self, package: &dyn IDexPackage, name: &str) -> Result<bool>;
+    /// Moves `class` to the package `package_fqname`, e.g. `com.example.ui`.
+    fn moveClassToPackage(&self, class: &dyn IDexClass, package_fqname: &str) -> Result<bool>;
+    fn getComment(&self, address: &str) -> Result<Option<String>>;
+    /// Sets the comment at `address`, an empty comment removes it.
+    fn setComment(&self, address: &str, comment: &str) -> Result<bool>;
+}
+pub trait IInteractiveUnitMarker<'a>: IInteractiveUnit<'a> {}
+
+fn rename_item(
+    unit: &dyn IInteractiveUnit,
+    item_id: i64,
+    address: &str,
+    name: &str,
+) -> Result<'static, bool> {
+    let context = ActionContext::new(unit, Actions::Rename, item_id, Some(address))?;
+    let data = ActionRenameData::new()?;
+    if !unit.canExecuteAction(&context)? || !unit.prepareExecution(&context, &data)? {
+        return Ok(false);
+    }
+    data.setNewName(name)?;
+    unit.executeAction(&context, &data)
+}
+
+impl<'a, T> IInteractiveUnit<'a> for T
+where
+    T: 'a + IInteractiveUnitMarker<'a> + IUnitMarker<'a> + Instance,
+{
+    fn canExecuteAction(&self, context: &ActionContext) -> Result<bool> {
+        let args = [context.get_obj()?.into()];
+        call!([Bool]self, "canExecuteAction", "(Lcom.pnfsoftware.jeb.core.actions.ActionContext;)Z", &args)
+    }
+    fn prepareExecution(&self, context: &ActionContext, data: &dyn IActionData) -> Result<bool> {
+        let args = [context.get_obj()?.into(), data.get_obj()?.into()];
+        call!([Bool]self, "prepareExecution", "(Lcom.pnfsoftware.jeb.core.actions.ActionContext;Lcom.pnfsoftware.jeb.core.actions.IActionData;)Z", &args)
+    }
+    fn executeAction(&self, context: &ActionContext, data: &dyn IActionData) -> Result<bool> {
+        let args = [context.get_obj()?.into(), data.get_obj()?.into()];
+        call!([Bool]self, "executeAction", "(Lcom.pnfsoftware.jeb.core.actions.ActionContext;Lcom.pnfsoftware.jeb.core.actions.IActionData;)Z", &args)
+    }
+    fn renameClass(&self, class: &dyn IDexClass, name: &str) -> Result<bool> {
+        rename_item(self, class.getItemId()?, &class.getAddress()?, name)
+    }
+    fn renameMethod(&self, method: &dyn IDexMethod, name: &str) -> Result<bool> {
+        rename_item(self, method.getItemId()?, &method.getAddress()?, name)
+    }
+    fn renameField(&self, field: &dyn IDexField, name: &str) -> Result<bool> {
+        rename_item(self, field.getItemId()?, &field.getAddress()?, name)
+    }
+    fn renamePackage(&self, package: &dyn IDexPackage, name: &str) -> Result<bool> {
+        rename_item(self, package.getItemId()?, &package.getAddress()?, name)
+    }
+    fn moveClassToPackage(&self, class: &dyn IDexClass, package_fqname: &str) -> Result<bool> {
+        let address = class.getAddress()?;
+        let context = ActionContext::new(self, Actions::MoveToPackage, class.getItemId()?, Some(&address))?;
+        let data = ActionMoveToPackageData::new()?;
+        if !self.canExecuteAction(&context)? || !self.prepareExecution(&context, &data)? {
+            return Ok(false);
+        }
+        data.setDstPackageFqname(package_fqname)?;
+        self.executeAction(&context, &data)
+    }
+    fn getComment(&self, address: &str) -> Result<Option<String>> {
+        let context = ActionContext::new(self, Actions::Comment, 0, Some(address))?;
+        let data = ActionCommentData::new()?;
+        if !self.prepareExecution(&context, &data)? {
+            return Ok(None);
+        }
+        data.getCurrentComment()
+    }
+    fn setComment(&self, address: &str, comment: &str) -> Result<bool> {
+        let context = ActionContext::new(self, Actions::Comment, 0, Some(address))?;
+        let data = ActionCommentData::new()?;
+        if !self.canExecuteAction(&context)? || !self.prepareExecution(&context, &data)? {
+            return Ok(false);
+        }
+        data.setNewComment(comment)?;
+        self.executeAction(&context, &data)
+    }
+}
+
+pub trait IXmlUnit<'a>: IUnit<'a> {
+    fn getDocument(&self) -> Result<Document<'_>>;
+}
+
+impl<'a> IXmlUnit<'a> for JebXmlUnit<'a> {
+    fn getDocument(&self) -> Result<Document<'_>> {
+        let res = call!(self, "getDocument", "()Lorg.w3c.dom.Document;", &[])?;
+        Ok(Document(res))
+    }
+}
+
+impl<'a, 'b> TryFrom<&'b dyn IUnit<'a>> for JebXmlUnit<'b> {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(
+        value: &'b dyn IUnit<'a>,
+    ) -> core::result::Result<Self, Self::Error> {
+        if value.getFormatType()? == "xml" {
+            Ok(JebXmlUnit(value.get_obj()?.into()))
+        } else {
+            Err("Not a XmlUnit".into())
+        }
+    }
+}
+
 impl<'a, 'b> TryFrom<&'b dyn IUnit<'a>> for JebDexUnit<'b> {
     type Error = Box<dyn std::error::Error>;
 
@@ -713,14 +2779,22 @@ impl<'a, 'b> TryFrom<&'b dyn IUnit<'a>> for JebDexUnit<'b> {
 }
 
 jclass! {JebInstruction, JebInstruction_}
+impl<'a> IInstructionMarker for JebInstruction<'a> {}
+jclass! {JebDalvikInstruction, JebDalvikInstruction_}
+impl<'a> IInstructionMarker for JebDalvikInstruction<'a> {}
 jclass! {JebUnit, JebUnit_}
 impl<'a> IUnitMarker<'a> for JebUnit<'a> {}
 
 jclass! {JebUnitProcessor, JebUnitProcessor_}
+jclass! {JebUnitNotificationManager, JebUnitNotificationManager_}
+jclass! {JebUnitNotification, JebUnitNotification_}
 jclass! {JebDexClass,JebDexClass_}
 impl<'a> IDexClassMarker<'a> for JebDexClass<'a> {}
+jclass! {JebXmlUnit, JebXmlUnit_}
+impl<'a> IUnitMarker<'a> for JebXmlUnit<'a> {}
 jclass! {JebDexUnit, JebDexUnit_}
 impl<'a> IDexUnitMarker<'a> for JebDexUnit<'a> {}
+impl<'a> IInteractiveUnitMarker<'a> for JebDexUnit<'a> {}
 impl<'a> IUnitMarker<'a> for JebDexUnit<'a> {}
 
 jclass! {JebDexMethod, JebDexMethod_}
@@ -731,11 +2805,30 @@ jclass! {AbstractUnit,AbstractUnit_}
 jclass! {AbstractCodeUnit,AbstractCodeUnit_}
 jclass! {JebDexAddress, JebDexAddress_}
 jclass! {JebDexString,JebDexString_}
+jclass! {JebDexField, JebDexField_}
+jclass! {JebDexType, JebDexType_}
+jclass! {JebDexPrototype, JebDexPrototype_}
+jclass! {JebDexMethodData, JebDexMethodData_}
+jclass! {JebDexCodeItem, JebDexCodeItem_}
+jclass! {JebDexExceptionItem, JebDexExceptionItem_}
+jclass! {JebDexExceptionHandler, JebDexExceptionHandler_}
+jclass! {JebDexDebugInfo, JebDexDebugInfo_}
+jclass! {JebDexDebugLine, JebDexDebugLine_}
+jclass! {JebDexDebugVariable, JebDexDebugVariable_}
+jclass! {JebDexAnnotationsDirectory, JebDexAnnotationsDirectory_}
+jclass! {JebDexAnnotationForField, JebDexAnnotationForField_}
+jclass! {JebDexAnnotationForMethod, JebDexAnnotationForMethod_}
+jclass! {JebDexAnnotationItem, JebDexAnnotationItem_}
+jclass! {JebDexAnnotation, JebDexAnnotation_}
+jclass! {JebDexAnnotationElement, JebDexAnnotationElement_}
 
 impl<'a> IDexAddress<'a> for JebDexAddress<'a> {
     fn getInternalAddress(&self) -> Result<String> {
         call!([String]self, "getInternalAddress", "()Ljava/lang/String;", &[])
     }
+    fn getDexAddress(&self) -> Result<DexAddress> {
+        DexAddress::parse(&self.getInternalAddress()?)
+    }
 }
 impl<'a> IDexString<'a> for JebDexString<'a> {
     fn getIndex(&self) -> Result<i32> {
@@ -755,6 +2848,110 @@ impl<'a> IDexString<'a> for JebDexString<'a> {
     }
 }
 
+impl<'a> IDexField<'a> for JebDexField<'a> {
+    fn getIndex(&self) -> Result<i32> {
+        call!([i32]self, "getIndex", "()I", &[])
+    }
+    fn getName(&self, effective: bool) -> Result<String> {
+        call!([String]self, "getName", normalize!("(Z)Ljava.lang.String;"), &[effective.into()])
+    }
+    fn getSignature(&self, effective: bool) -> Result<String> {
+        call!([String]self, "getSignature", normalize!("(Z)Ljava.lang.String;"), &[effective.into()])
+    }
+    fn getGenericFlags(&self) -> Result<i32> {
+        call!([i32]self, "getGenericFlags", "()I", &[])
+    }
+    fn getClassTypeIndex(&self) -> Result<i32> {
+        call!([i32]self, "getClassTypeIndex", "()I", &[])
+    }
+    fn getClassType(&self) -> Result<Box<dyn IDexType + '_>> {
+        let res = call!(self, "getClassType", "()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexType;", &[])?;
+        box_ok!(JebDexType(res))
+    }
+    fn getFieldTypeIndex(&self) -> Result<i32> {
+        call!([i32]self, "getFieldTypeIndex", "()I", &[])
+    }
+    fn getFieldType(&self) -> Result<Box<dyn IDexType + '_>> {
+        let res = call!(self, "getFieldType", "()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexType;", &[])?;
+        box_ok!(JebDexType(res))
+    }
+    fn getStaticInitializer(&self) -> Result<Option<DexValue>> {
+        let env = get_vm!();
+        let res = env
+            .call_method(self.get_obj()?, "getStaticInitializer", normalize!("()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexValue;"), &[])?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(DexValue::from_object(res)?))
+    }
+    fn isInternal(&self) -> Result<bool> {
+        call!([Bool]self, "isInternal", "()Z", &[])
+    }
+    fn getAccessFlags(&self) -> Result<AccessFlags> {
+        let env = get_vm!();
+        let data = env
+            .call_method(self.get_obj()?, "getData", normalize!("()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexFieldData;"), &[])?
+            .l()?;
+        if data.is_null() {
+            return Ok(AccessFlags::empty());
+        }
+        let flags = env.call_method(data, "getAccessFlags", "()I", &[])?.i()?;
+        Ok(flags.into())
+    }
+    fn getItemId(&self) -> Result<i64> {
+        call!([i64]self, "getItemId", "()J", &[])
+    }
+    fn getAddress(&self) -> Result<String> {
+        call!([String]self, "getAddress", "()Ljava/lang/String;", &[])
+    }
+}
+
+impl<'a> IDexType<'a> for JebDexType<'a> {
+    fn getIndex(&self) -> Result<i32> {
+        call!([i32]self, "getIndex", "()I", &[])
+    }
+    fn getName(&self, effective: bool) -> Result<String> {
+        call!([String]self, "getName", normalize!("(Z)Ljava.lang.String;"), &[effective.into()])
+    }
+    fn getSignature(&self, effective: bool) -> Result<String> {
+        call!([String]self, "getSignature", normalize!("(Z)Ljava.lang.String;"), &[effective.into()])
+    }
+    fn getImplementingClass(&self) -> Result<Option<Box<dyn IDexClass + '_>>> {
+        let env = get_vm!();
+        let res = env
+            .call_method(self.get_obj()?, "getImplementingClass", normalize!("()Lcom.pnfsoftware.jeb.core.units.code.android.dex.IDexClass;"), &[])?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(JebDexClass(res.into()))))
+    }
+}
+
+impl<'a> IDexPrototype<'a> for JebDexPrototype<'a> {
+    fn getIndex(&self) -> Result<i32> {
+        call!([i32]self, "getIndex", "()I", &[])
+    }
+    fn getShorty(&self) -> Result<String> {
+        call!([String]self, "getShorty", "()Ljava/lang/String;", &[])
+    }
+    fn getReturnTypeIndex(&self) -> Result<i32> {
+        call!([i32]self, "getReturnTypeIndex", "()I", &[])
+    }
+    fn getParameterTypeIndexes(&self) -> Result<Vec<i32>> {
+        let res = call!(self, "getParameterTypeIndexes", "()[I", &[])?;
+        let env = get_vm!();
+        let array = res.l()?.into_inner();
+        let mut indexes = vec![0; env.get_array_length(array)? as usize];
+        env.get_int_array_region(array, 0, &mut indexes)?;
+        Ok(indexes)
+    }
+    fn generate(&self, effective: bool) -> Result<String> {
+        call!([String]self, "generate", normalize!("(Z)Ljava.lang.String;"), &[effective.into()])
+    }
+}
+
 impl<'a> IDexReferenceManager<'a> for JebDexReferenceManager<'a> {
     jcall! {
         Vec[normalize!("(Lcom.pnfsoftware.jeb.core.units.code.android.dex.DexPoolType;I)Ljava/util/Collection;")]
@@ -765,6 +2962,45 @@ impl<'a> IDexReferenceManager<'a> for JebDexReferenceManager<'a> {
     }
 }
 
+impl<'a> IUnitNotificationManager<'a> for JebUnitNotificationManager<'a> {
+    jcall! {
+        Vec<IUnitNotification>
+        [JebUnitNotification]
+        fn getNotifications()
+    }
+    fn getNotificationCount(&self) -> Result<i32> {
+        call!([i32]self, "getNotificationCount", "()I", &[])
+    }
+}
+
+impl<'a> IUnitNotification<'a> for JebUnitNotification<'a> {
+    fn getType(&self) -> Result<NotificationType> {
+        let res = call!(self, "getType", "()Lcom.pnfsoftware.jeb.core.units.NotificationType;", &[])?;
+        NotificationType::from_object(res.l()?)
+    }
+    fn getLevel(&self) -> Result<i32> {
+        let res = call!(self, "getType", "()Lcom.pnfsoftware.jeb.core.units.NotificationType;", &[])?;
+        call_object!([i32]res.l()?, "getLevel", "()I", &[])
+    }
+    fn getDescription(&self) -> Result<String> {
+        call!([String]self, "getDescription", "()Ljava/lang/String;", &[])
+    }
+    fn getAddress(&self) -> Result<Option<String>> {
+        let env = get_vm!();
+        let res = env
+            .call_method(self.get_obj()?, "getAddress", "()Ljava/lang/String;", &[])?
+            .l()?;
+        if res.is_null() {
+            return Ok(None);
+        }
+        let address: String = env.get_string(res.into())?.into();
+        Ok(Some(address))
+    }
+    fn getKey(&self) -> Result<String> {
+        call!([String]self, "getKey", "()Ljava/lang/String;", &[])
+    }
+}
+
 impl<'a> IUnitProcessor<'a> for JebUnitProcessor<'a> {
     fn createDebugger(
         &self,
@@ -849,4 +3085,72 @@ where
         call!(self, "process", "()V", &[])?;
         Ok(())
     }
+    fn getParent(&self) -> Result<Box<dyn IUnit + '_>> {
+        let res = call!(self, "getParent", "()Lcom.pnfsoftware.jeb.core.IUnitCreator;", &[])?;
+        let env = get_vm!();
+        let class = env.find_class(normalize!("com.pnfsoftware.jeb.core.units.IUnit"))?;
+        if env.is_instance_of(res.l()?, class)? {
+            box_ok!(JebUnit(res))
+        } else {
+            Err("Parent is not a unit".into())
+        }
+    }
+    fn getRealName(&self) -> Result<String> {
+        call!([String] self, "getRealName", "()Ljava.lang.String;", &[])
+    }
+    fn getUid(&self) -> Result<i64> {
+        call!([i64] self, "getUid", "()J", &[])
+    }
+    fn getPropertyDefinitionManager(
+        &self,
+    ) -> Result<Box<dyn IPropertyDefinitionManager + '_>> {
+        let res = call!(self, "getPropertyDefinitionManager", "()Lcom.pnfsoftware.jeb.core.properties.IPropertyDefinitionManager;", &[])?;
+        box_ok!(PropertyDefinitionManager(res))
+    }
+    fn getPropertyManager(&self) -> Result<Box<dyn IPropertyManager + '_>> {
+        let res = call!(self, "getPropertyManager", "()Lcom.pnfsoftware.jeb.core.properties.IPropertyManager;", &[])?;
+        box_ok!(PropertyManager(res))
+    }
+    fn getInput(&self) -> Result<Box<dyn IInput + '_>> {
+        let res = call!(self, "getInput", "()Lcom.pnfsoftware.jeb.core.input.IInput;", &[])?;
+        box_ok!(JebInput(res))
+    }
+    fn getCreationTimestamp(&self) -> Result<i64> {
+        call!([i64] self, "getCreationTimestamp", "()J", &[])
+    }
+    fn canBePersisted(&self) -> Result<bool> {
+        call!([Bool] self, "canBePersisted", "()Z", &[])
+    }
+    fn getFormatter(&self) -> Result<Box<dyn IUnitFormatter + '_>> {
+        let res = call!(self, "getFormatter", "()Lcom.pnfsoftware.jeb.core.output.IUnitFormatter;", &[])?;
+        box_ok!(JebUnitFormatter(res))
+    }
+    fn getNotificationManager(&self) -> Result<Box<dyn IUnitNotificationManager + '_>> {
+        let res = call!(self, "getNotificationManager", "()Lcom.pnfsoftware.jeb.core.units.IUnitNotificationManager;", &[])?;
+        box_ok!(JebUnitNotificationManager(res))
+    }
+    fn getPath(&self) -> Result<String> {
+        let env = get_vm!();
+        let unit_class = env.find_class(normalize!("com.pnfsoftware.jeb.core.units.IUnit"))?;
+        let mut names = vec![];
+        let mut current = self.get_obj()?;
+        loop {
+            let name = env.call_method(current, "getName", "()Ljava/lang/String;", &[])?.l()?;
+            let name: String = env.get_string(name.into())?.into();
+            names.push(name);
+            let parent = env
+                .call_method(current, "getParent", normalize!("()Lcom.pnfsoftware.jeb.core.IUnitCreator;"), &[])?
+                .l()?;
+            // the parent of a top-level unit is its artifact
+            if parent.is_null() || !env.is_instance_of(parent, unit_class)? {
+                break;
+            }
+            current = parent;
+        }
+        names.reverse();
+        Ok(names.join("/"))
+    }
+    fn walk(&self) -> Result<UnitWalker<'_>> {
+        UnitWalker::new(self)
+    }
 }
\ No newline at end of file
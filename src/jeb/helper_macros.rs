@@ -479,7 +479,6 @@ macro_rules! call {
     ($obj:expr, $name:expr, $signature:expr, $args:expr) => {
         if let Ok(obj) = $obj.get_obj() {
            let res = call_object!(obj, $name, $signature, $args)?;
-           let env = get_vm!();
 
            if is_null!(res) {
             Err("Null pointer".into())
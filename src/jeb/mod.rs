@@ -20,6 +20,8 @@ pub mod helper_macros;
 pub mod file;
 pub mod list;
 pub mod com;
+pub mod processing;
+pub mod analysis;
 
 const JSTRING : &str = "java/lang/String";
 const JOBJECT : &str = "java/lang/Object";
@@ -302,6 +304,53 @@ pub trait Instance {
 }
 
 pub mod org {
+    pub mod w3c {
+        pub mod dom {
+            static PACKAGE_NAME: &str = "org/w3c/dom";
+            use crate::jeb::*;
+
+            jclass! {Document, Document_}
+            jclass! {Element, Element_}
+            jclass! {NodeList, NodeList_}
+
+            impl<'a> Document<'a> {
+                pub fn getDocumentElement(&self) -> Result<Element<'_>> {
+                    let res = call!(self, "getDocumentElement", "()Lorg.w3c.dom.Element;", &[])?;
+                    Ok(Element(res))
+                }
+                pub fn getElementsByTagName(&self, name: &str) -> Result<NodeList<'_>> {
+                    let env = get_vm!();
+                    let args = [env.new_string(name)?.into()];
+                    let res = call!(self, "getElementsByTagName", "(Ljava.lang.String;)Lorg.w3c.dom.NodeList;", &args)?;
+                    Ok(NodeList(res))
+                }
+            }
+
+            impl<'a> Element<'a> {
+                pub fn getTagName(&self) -> Result<String> {
+                    call!([String]self, "getTagName", "()Ljava.lang.String;", &[])
+                }
+                /// The attribute value, empty if the element has no such attribute.
+                pub fn getAttribute(&self, name: &str) -> Result<String> {
+                    let env = get_vm!();
+                    let args = [env.new_string(name)?.into()];
+                    call!([String]self, "getAttribute", "(Ljava.lang.String;)Ljava.lang.String;", &args)
+                }
+            }
+
+            impl<'a> NodeList<'a> {
+                pub fn getLength(&self) -> Result<i32> {
+                    call!([i32]self, "getLength", "()I", &[])
+                }
+                /// The node at `index`, as an element since lists returned by
+                /// `getElementsByTagName` only hold elements.
+                pub fn item(&self, index: i32) -> Result<Element<'_>> {
+                    let res = call!(self, "item", "(I)Lorg.w3c.dom.Node;", &[index.into()])?;
+                    Ok(Element(res))
+                }
+            }
+        }
+    }
     pub mod apache {
         pub mod commons {
             pub mod configuration2 {
@@ -0,0 +1,276 @@
+// Copyright (c) 2020 Patrick Amrein <amren@ubique.ch>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Processing of units with progress reporting, cancellation and timeouts. `IUnit::process`
+//! blocks until JEB is done, so the processing is moved to a worker thread and the calling thread
+//! follows the `UnitCreated`/`UnitProcessed` events JEB sends while processing.
+
+use crate::jeb::*;
+use crate::jeb::com::pnfsoftware::jeb::core::units::{IUnit, JebUnit};
+use crate::jeb::com::pnfsoftware::jeb::util::events::{IEvent, JebEvent};
+use crate::jeb::events::JebEventType;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+define_jclass!(
+    (sender : Sender<ProcessingMessage>)
+    package ch.ubique;
+
+    public class UnitProcessingListener implements com.pnfsoftware.jeb.util.events.IEventListener {
+        public native void onEvent(com.pnfsoftware.jeb.util.events.IEvent e);
+    }
+);
+
+pub enum ProcessingMessage {
+    UnitCreated,
+    UnitProcessed,
+    Done(core::result::Result<(), String>),
+}
+
+impl<'a> IUnitProcessingListener for UnitProcessingListener<'a> {
+    fn on_event(&self, e: jni::objects::JObject) {
+        let jeb_event = JebEvent(e.into());
+        if let Ok(data) = jeb_event.getType() {
+            match data.into() {
+                JebEventType::UnitCreated => {
+                    let _ = self.1.send(ProcessingMessage::UnitCreated);
+                }
+                JebEventType::UnitProcessed => {
+                    let _ = self.1.send(ProcessingMessage::UnitProcessed);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Cancels a running `process_unit` call, can be handed to other threads.
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Default)]
+pub struct ProcessingOptions {
+    /// Wall-clock time after which processing is aborted.
+    pub timeout: Option<Duration>,
+    pub cancellation: Option<CancellationToken>,
+    /// How long to wait for JEB to stop after a cancellation or timeout, 30 seconds by default.
+    pub abort_timeout: Option<Duration>,
+}
+
+const DEFAULT_ABORT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The number of units JEB created and processed so far, children included.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessingProgress {
+    pub created: usize,
+    pub processed: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessingOutcome {
+    Completed,
+    Cancelled,
+    TimedOut,
+}
+
+/// A unit of the processed tree which is not processed, with the status JEB gave it.
+#[derive(Debug, Clone)]
+pub struct ProcessingFailure {
+    pub unit_path: String,
+    /// `None` if JEB set no status message on the unit.
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessingReport {
+    pub outcome: ProcessingOutcome,
+    pub progress: ProcessingProgress,
+    pub failures: Vec<ProcessingFailure>,
+}
+
+/// `IUnit::getStatus` fails on units without a status, which is the common case for a failed unit.
+fn unit_status(unit: &dyn IUnit) -> Result<'static, Option<String>> {
+    let env = get_vm!();
+    let res = env
+        .call_method(unit.get_obj()?, "getStatus", "()Ljava/lang/String;", &[])?
+        .l()?;
+    if res.is_null() {
+        return Ok(None);
+    }
+    let status: String = env.get_string(res.into())?.into();
+    Ok(Some(status))
+}
+
+/// Processes `unit` and reports progress to `on_progress` every time JEB created or processed a
+/// unit. On cancellation or timeout the JEB worker thread is interrupted and the function waits up
+/// to `ProcessingOptions::abort_timeout` for JEB to stop, so that the unit tree is not inspected
+/// while it is still being processed; JEB may keep the unit partially processed in that case.
+pub fn process_unit<F>(
+    unit: &dyn IUnit,
+    options: &ProcessingOptions,
+    mut on_progress: F,
+) -> Result<'static, ProcessingReport>
+where
+    F: FnMut(&ProcessingProgress),
+{
+    let env = get_vm!();
+    let (tx, rx) = mpsc::channel();
+    let (thread_tx, thread_rx) = mpsc::sync_channel(1);
+    let (start_tx, start_rx) = mpsc::sync_channel::<()>(1);
+    let unit_ref = env.new_global_ref(unit.get_obj()?)?;
+    let worker_tx = tx.clone();
+    let worker = std::thread::spawn(move || {
+        let env = get_vm_unwrap!();
+        // the java thread is handed over before processing starts, so it can always be interrupted
+        let thread = env
+            .call_static_method("java/lang/Thread", "currentThread", "()Ljava/lang/Thread;", &[])
+            .and_then(|thread| thread.l())
+            .and_then(|thread| env.new_global_ref(thread))
+            .map_err(|e| e.to_string());
+        let has_thread = thread.is_ok();
+        let _ = thread_tx.send(thread);
+        if !has_thread || start_rx.recv().is_err() {
+            return;
+        }
+        let unit = JebUnit(unit_ref.as_obj().into());
+        let result = unit.process().map_err(|e| e.to_string());
+        let _ = worker_tx.send(ProcessingMessage::Done(result));
+    });
+    let worker_thread = match thread_rx.recv() {
+        Ok(thread) => thread?,
+        Err(_) => return Err("processing thread died".into()),
+    };
+
+    // dropping `start_tx` on error makes the worker return without processing
+    let listener = UnitProcessingListener::new(tx)?;
+    let listener_args = [listener.get_obj()?.into()];
+    call!(unit, "addListener", "(Lcom.pnfsoftware.jeb.util.events.IEventListener;)V", &listener_args)?;
+    start_tx.send(()).map_err(|_| "processing thread died")?;
+
+    let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+    let mut progress = ProcessingProgress::default();
+    let mut count = |message: &ProcessingMessage, progress: &mut ProcessingProgress| match message {
+        ProcessingMessage::UnitCreated => {
+            progress.created += 1;
+            on_progress(progress);
+        }
+        ProcessingMessage::UnitProcessed => {
+            progress.processed += 1;
+            on_progress(progress);
+        }
+        ProcessingMessage::Done(_) => {}
+    };
+    let outcome = loop {
+        match rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(ProcessingMessage::Done(Ok(()))) => break Ok(ProcessingOutcome::Completed),
+            Ok(ProcessingMessage::Done(Err(e))) => break Err(e),
+            Ok(message) => count(&message, &mut progress),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break Err("processing thread died".to_string()),
+        }
+        if options.cancellation.as_ref().is_some_and(|token| token.is_cancelled()) {
+            break Ok(ProcessingOutcome::Cancelled);
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            break Ok(ProcessingOutcome::TimedOut);
+        }
+    };
+
+    let mut stopped = true;
+    let mut interrupted = Ok(());
+    if let Ok(ProcessingOutcome::Cancelled) | Ok(ProcessingOutcome::TimedOut) = outcome {
+        interrupted = env
+            .call_method(worker_thread.as_obj(), "interrupt", "()V", &[])
+            .map(|_| ());
+        // the result of an interrupted processing does not matter, only that it ended
+        let abort_timeout = options.abort_timeout.unwrap_or(DEFAULT_ABORT_TIMEOUT);
+        let abort_deadline = Instant::now() + abort_timeout;
+        stopped = false;
+        while let Some(remaining) = abort_deadline.checked_duration_since(Instant::now()) {
+            match rx.recv_timeout(remaining) {
+                Ok(ProcessingMessage::Done(_)) | Err(RecvTimeoutError::Disconnected) => {
+                    stopped = true;
+                    break;
+                }
+                Ok(message) => count(&message, &mut progress),
+                Err(RecvTimeoutError::Timeout) => break,
+            }
+        }
+    }
+    call!(unit, "removeListener", "(Lcom.pnfsoftware.jeb.util.events.IEventListener;)V", &listener_args)?;
+    interrupted?;
+    if !stopped {
+        return Err("JEB did not stop processing after being interrupted".into());
+    }
+    worker.join().map_err(|_| "processing thread panicked")?;
+
+    let outcome = outcome?;
+    let mut failures = vec![];
+    for entry in unit.walk()? {
+        let (_, child) = entry?;
+        if !child.isProcessed()? {
+            failures.push(ProcessingFailure {
+                unit_path: child.getPath()?,
+                status: unit_status(child.as_ref())?,
+            });
+        }
+    }
+    Ok(ProcessingReport {
+        outcome,
+        progress,
+        failures,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancellation_is_shared_between_clones() {
+        let token = CancellationToken::new();
+        let handed_out = token.clone();
+        assert!(!token.is_cancelled());
+        handed_out.cancel();
+        assert!(token.is_cancelled());
+        assert!(handed_out.is_cancelled());
+        assert!(!CancellationToken::new().is_cancelled());
+    }
+
+    #[test]
+    fn cancellation_across_threads() {
+        let token = CancellationToken::new();
+        let handed_out = token.clone();
+        std::thread::spawn(move || handed_out.cancel()).join().unwrap();
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn default_options() {
+        let options = ProcessingOptions::default();
+        assert!(options.timeout.is_none());
+        assert!(options.cancellation.is_none());
+        assert!(options.abort_timeout.is_none());
+        assert_eq!(DEFAULT_ABORT_TIMEOUT, Duration::from_secs(30));
+        let progress = ProcessingProgress::default();
+        assert_eq!((progress.created, progress.processed), (0, 0));
+    }
+}